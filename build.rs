@@ -1,5 +1,7 @@
+#[cfg(feature = "gdal-backend")]
 use semver::Version;
 
+#[cfg(feature = "gdal-backend")]
 pub fn gdal_version_info(key: &str) -> String {
     let c_key = std::ffi::CString::new(key.as_bytes()).unwrap();
     let version_string = unsafe {
@@ -11,6 +13,13 @@ pub fn gdal_version_info(key: &str) -> String {
 }
 
 fn main() {
+    // gdal version flags are only required by the gdal backend
+    #[cfg(feature = "gdal-backend")]
+    detect_gdal_version();
+}
+
+#[cfg(feature = "gdal-backend")]
+fn detect_gdal_version() {
     // This expects GDAL to repond with "GDAL Semver , RELEASE DATE"
     let gdal_version_string = gdal_version_info("--version"); 
     //println!("GDAL version string: \"{}\"", gdal_version_string);
@@ -0,0 +1,145 @@
+#[cfg(feature = "gdal-backend")]
+use gdal::Dataset;
+use ndarray::{Array3, Axis};
+
+use crate::backend::Backend;
+use crate::coordinate::GeoTransform;
+use crate::raster::{AnyRaster, Raster, RasterType};
+
+use std::borrow::Cow;
+use std::error::Error;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeoArray<T> {
+    array: Array3<T>,
+    no_data_value: Option<f64>,
+    projection: String,
+    transform: GeoTransform,
+}
+
+impl<T: RasterType> GeoArray<T> {
+    pub fn new(array: Array3<T>, transform: GeoTransform,
+            projection: String, no_data_value: Option<f64>) -> GeoArray<T> {
+        GeoArray { array, no_data_value, projection, transform }
+    }
+
+    pub fn array(&self) -> &Array3<T> {
+        &self.array
+    }
+
+    pub fn array_mut(&mut self) -> &mut Array3<T> {
+        &mut self.array
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub fn from_dataset(dataset: &Dataset)
+            -> Result<GeoArray<T>, Box<dyn Error>> {
+        let raster = dataset.to_raster()?;
+        GeoArray::from_raster(raster.into_owned())
+    }
+
+    pub fn geo_transform(&self) -> GeoTransform {
+        self.transform
+    }
+
+    pub fn into_array(self) -> Array3<T> {
+        self.array
+    }
+
+    pub fn no_data_value(&self) -> Option<f64> {
+        self.no_data_value
+    }
+
+    pub fn projection(&self) -> &str {
+        &self.projection
+    }
+
+    pub fn raster_count(&self) -> usize {
+        self.array.len_of(Axis(0))
+    }
+
+    pub fn raster_size(&self) -> (usize, usize) {
+        (self.array.len_of(Axis(2)), self.array.len_of(Axis(1)))
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub fn to_dataset(&self) -> Result<Dataset, Box<dyn Error>> {
+        let raster = self.to_raster()?;
+        raster.to_dataset()
+    }
+}
+
+impl<T: RasterType> Backend for GeoArray<T> {
+    fn from_raster(raster: AnyRaster) -> Result<Self, Box<dyn Error>> {
+        let raster = T::from_any(raster)?;
+        let array = raster.to_array()?;
+
+        Ok(GeoArray::new(array, raster.geo_transform(),
+            raster.projection().to_string(), raster.no_data_value()))
+    }
+
+    fn to_raster(&self) -> Result<Cow<'_, AnyRaster>, Box<dyn Error>> {
+        let raster = Raster::from_array(&self.array, self.transform,
+            self.projection.clone(), self.no_data_value)?;
+
+        Ok(Cow::Owned(T::into_any(raster)))
+    }
+}
+
+impl<T: RasterType> Raster<T> {
+    pub fn from_array(array: &Array3<T>, transform: GeoTransform,
+            projection: String, no_data_value: Option<f64>)
+            -> Result<Raster<T>, Box<dyn Error>> {
+        // copy bands in logical order regardless of array layout
+        let (_, height, width) = array.dim();
+        let bands = array.outer_iter()
+            .map(|x| x.iter().cloned().collect()).collect();
+
+        Raster::new(width, height, bands,
+            transform, projection, no_data_value)
+    }
+
+    pub fn to_array(&self) -> Result<Array3<T>, Box<dyn Error>> {
+        // bands are stored in (band, row, col) order
+        let (width, height) = self.raster_size();
+        let data = self.bands().concat();
+
+        Ok(Array3::from_shape_vec(
+            (self.raster_count(), height, width), data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GeoArray;
+    use crate::coordinate::GeoTransform;
+
+    use ndarray::Array3;
+
+    const TRANSFORM: GeoTransform =
+        GeoTransform::new(0.0, 1.0, 0.0, 2.0, 0.0, -1.0);
+
+    #[test]
+    fn array_fill() {
+        let array = Array3::from_shape_vec((2, 2, 2),
+            vec![0u16, 1, 0, 0, 0, 1, 2, 0]).expect("init array");
+        let fill_array = Array3::from_shape_vec((2, 2, 2),
+            vec![3u16, 3, 3, 0, 4, 4, 4, 0]).expect("init array");
+
+        let geo_array = GeoArray::new(array, TRANSFORM,
+            String::new(), Some(0.0));
+        let fill_geo_array = GeoArray::new(fill_array, TRANSFORM,
+            String::new(), Some(0.0));
+
+        assert_eq!(crate::get_coverage(&geo_array)
+            .expect("array coverage"), 0.5);
+
+        // fill array and validate georeferencing is maintained
+        let result = crate::fill(&[geo_array, fill_geo_array])
+            .expect("fill arrays");
+        assert_eq!(result.geo_transform(), TRANSFORM);
+        assert_eq!(result.raster_size(), (2, 2));
+        assert_eq!(result.array().as_slice().expect("array slice"),
+            &[3, 1, 0, 0, 4, 1, 2, 0]);
+    }
+}
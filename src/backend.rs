@@ -0,0 +1,157 @@
+#[cfg(feature = "gdal-backend")]
+use gdal::Dataset;
+#[cfg(feature = "gdal-backend")]
+use gdal::raster::{GdalType, RasterBand};
+#[cfg(feature = "gdal-backend")]
+use gdal_sys::GDALDataType;
+
+#[cfg(feature = "gdal-backend")]
+use crate::FromPrimitive;
+use crate::raster::AnyRaster;
+
+use std::borrow::Cow;
+use std::error::Error;
+
+pub trait Backend: Sized {
+    fn from_raster(raster: AnyRaster) -> Result<Self, Box<dyn Error>>;
+    fn to_raster(&self) -> Result<Cow<'_, AnyRaster>, Box<dyn Error>>;
+
+    fn get_invalid_pixels(&self) -> Result<Vec<bool>, Box<dyn Error>> {
+        let raster = self.to_raster()?;
+        Ok(raster.get_invalid_pixels())
+    }
+
+    fn no_data_values(&self) -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        // rasters share a single no_data value across rasterbands
+        let raster = self.to_raster()?;
+        Ok(vec![raster.no_data_value(); raster.raster_count()])
+    }
+}
+
+impl Backend for AnyRaster {
+    fn from_raster(raster: AnyRaster) -> Result<Self, Box<dyn Error>> {
+        Ok(raster)
+    }
+
+    fn to_raster(&self) -> Result<Cow<'_, AnyRaster>, Box<dyn Error>> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+#[cfg(feature = "gdal-backend")]
+impl Backend for Dataset {
+    fn from_raster(raster: AnyRaster) -> Result<Self, Box<dyn Error>> {
+        raster.to_dataset()
+    }
+
+    fn to_raster(&self) -> Result<Cow<'_, AnyRaster>, Box<dyn Error>> {
+        Ok(Cow::Owned(AnyRaster::from_dataset(self)?))
+    }
+
+    fn get_invalid_pixels(&self) -> Result<Vec<bool>, Box<dyn Error>> {
+        // read rasterbands individually so that datasets without a
+        //   transform or with mixed band types are supported
+        let (width, height) = self.raster_size();
+        let mut invalid_pixels = vec![true; width * height];
+        for i in 0..self.raster_count() {
+            let rasterband = self.rasterband(i+1)?;
+            let no_data_value = rasterband.no_data_value().unwrap_or(0.0);
+
+            match rasterband.band_type() {
+                GDALDataType::GDT_Byte => _get_invalid_pixels::<u8>(
+                    &rasterband, no_data_value, &mut invalid_pixels)?,
+                GDALDataType::GDT_Int16 => _get_invalid_pixels::<i16>(
+                    &rasterband, no_data_value, &mut invalid_pixels)?,
+                GDALDataType::GDT_UInt16 => _get_invalid_pixels::<u16>(
+                    &rasterband, no_data_value, &mut invalid_pixels)?,
+                GDALDataType::GDT_Float32 => _get_invalid_pixels::<f32>(
+                    &rasterband, no_data_value, &mut invalid_pixels)?,
+                band_type => return Err(format!(
+                    "unsupported band type '{}'", band_type).into()),
+            }
+        }
+
+        Ok(invalid_pixels)
+    }
+
+    fn no_data_values(&self) -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        let mut no_data_values = Vec::new();
+        for i in 0..self.raster_count() {
+            no_data_values.push(self.rasterband(i+1)?.no_data_value());
+        }
+
+        Ok(no_data_values)
+    }
+}
+
+#[cfg(feature = "gdal-backend")]
+fn _get_invalid_pixels<T: Copy + FromPrimitive + GdalType + PartialEq>(
+        rasterband: &RasterBand, no_data_value: f64,
+        invalid_pixels: &mut [bool]) -> Result<(), Box<dyn Error>> {
+    let no_data_value = T::from_f64(no_data_value);
+
+    // pixels are invalid if all bands are no_data
+    let buffer = rasterband.read_band_as::<T>()?;
+    for (i, pixel) in buffer.data.iter().enumerate() {
+        if *pixel != no_data_value {
+            invalid_pixels[i] = false;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn backend_dataset_coverage() {
+        use gdal::Driver;
+        use gdal::raster::Buffer;
+        use gdal_sys::{GDALDataType, GDAL_GCP};
+
+        use std::ffi::CString;
+
+        // initialize dataset with mixed band types and no transform
+        let driver = Driver::get("Mem").expect("get driver");
+        let dataset = driver.create_with_band_type::<u8>(
+            "", 2, 2, 1).expect("create dataset");
+        unsafe {
+            gdal_sys::GDALAddBand(dataset.c_dataset(),
+                GDALDataType::GDT_Int16, std::ptr::null_mut());
+        }
+
+        // each rasterband uses its own no_data value
+        let rasterband = dataset.rasterband(1).expect("rasterband");
+        rasterband.set_no_data_value(0.0).expect("set no_data value");
+        rasterband.write::<u8>((0, 0), (2, 2),
+            &Buffer::new((2, 2), vec![0, 0, 1, 0]))
+            .expect("write rasterband");
+
+        let rasterband = dataset.rasterband(2).expect("rasterband");
+        rasterband.set_no_data_value(-1.0).expect("set no_data value");
+        rasterband.write::<i16>((0, 0), (2, 2),
+            &Buffer::new((2, 2), vec![-1, 5, -1, -1]))
+            .expect("write rasterband");
+
+        // georeference dataset with GCPs only
+        let c_id = CString::new("").expect("gcp id");
+        let gcps: Vec<GDAL_GCP> = [(0.0, 0.0), (2.0, 0.0), (0.0, 2.0)]
+            .iter().map(|(x, y)| GDAL_GCP {
+                pszId: c_id.as_ptr() as *mut _,
+                pszInfo: c_id.as_ptr() as *mut _,
+                dfGCPPixel: *x,
+                dfGCPLine: *y,
+                dfGCPX: *x,
+                dfGCPY: -*y,
+                dfGCPZ: 0.0,
+            }).collect();
+        unsafe {
+            gdal_sys::GDALSetGCPs(dataset.c_dataset(), gcps.len() as _,
+                gcps.as_ptr(), c_id.as_ptr());
+        }
+
+        assert!(dataset.geo_transform().is_err());
+        assert_eq!(crate::get_coverage(&dataset).expect("coverage"), 0.5);
+    }
+}
@@ -0,0 +1,385 @@
+use clap::{App, Arg, ArgMatches};
+use gdal::Dataset;
+use st_image::coordinate::Geocode;
+use st_image::render::{ImageFormat, RenderOptions, Stretch};
+use st_image::store::{TilePriority, TileQuery, TileStore};
+use st_image::transform::SplitOptions;
+
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+
+enum Format {
+    Image(ImageFormat),
+    Serialized,
+}
+
+enum Source {
+    Directory(PathBuf),
+    Store(TileStore, TileQuery, Geocode),
+}
+
+impl Source {
+    fn get_dataset(&self, code: &str, tile_size: usize)
+            -> Result<Option<Dataset>, Box<dyn Error>> {
+        let (min_x, max_x, min_y, max_y) =
+            Geocode::QuadTile.get_window(code)?;
+        let epsg_code = Geocode::QuadTile.get_epsg_code();
+
+        let dataset = match self {
+            Source::Directory(directory) => {
+                // tiles are named by quadkey
+                let path = directory.join(format!("{}.tif", code));
+                match path.exists() {
+                    true => Dataset::open(&path)?,
+                    false => return Ok(None),
+                }
+            },
+            Source::Store(store, query, geocode) => {
+                // mosaic store tiles over the quadtile window
+                match store.mosaic((min_x, max_x, min_y, max_y),
+                        epsg_code, *geocode, query,
+                        TilePriority::Recency)? {
+                    Some(dataset) => dataset,
+                    None => return Ok(None),
+                }
+            },
+        };
+
+        // warp onto the exact quadtile window and dimensions
+        let options = SplitOptions {
+            dimensions: Some((tile_size, tile_size)),
+            ..Default::default()
+        };
+
+        let tile = st_image::transform::split_tile(&dataset,
+            min_x, max_x, min_y, max_y, epsg_code, &options)?;
+        Ok(tile.map(|x| x.dataset))
+    }
+}
+
+fn main() {
+    let matches = App::new("st-image-server")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("serve spatiotemporal image tiles over http")
+        .arg(Arg::with_name("DIRECTORY").required(true)
+            .help("directory of quadkey named tiles or tile store"))
+        .arg(Arg::with_name("address")
+            .short("a").long("address").takes_value(true)
+            .default_value("127.0.0.1:8080").help("listen address"))
+        .arg(Arg::with_name("bands")
+            .short("b").long("bands").takes_value(true)
+            .default_value("1,2,3").help("rendered band combination"))
+        .arg(Arg::with_name("stretch")
+            .short("s").long("stretch").takes_value(true)
+            .default_value("0,255").help("linear stretch 'min,max'"))
+        .arg(Arg::with_name("tile_size")
+            .short("t").long("tile-size").takes_value(true)
+            .default_value("256").help("rendered tile size"))
+        .arg(Arg::with_name("store")
+            .long("store").help("serve tiles from a tile store"))
+        .arg(Arg::with_name("geocode")
+            .short("g").long("geocode").takes_value(true)
+            .possible_values(&["geohash", "quadtile"]).requires("store")
+            .help("tile store geocode system [default: geohash]"))
+        .arg(Arg::with_name("platform")
+            .short("p").long("platform").takes_value(true)
+            .requires("store").help("tile store platform"))
+        .arg(Arg::with_name("min_timestamp")
+            .long("min-timestamp").takes_value(true)
+            .requires("store").help("tile store minimum timestamp"))
+        .arg(Arg::with_name("max_timestamp")
+            .long("max-timestamp").takes_value(true)
+            .requires("store").help("tile store maximum timestamp"))
+        .get_matches();
+
+    if let Err(e) = serve(&matches) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn serve(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    // initialize tile source
+    let directory = Path::new(matches.value_of("DIRECTORY").unwrap());
+    let source = match matches.is_present("store") {
+        true => {
+            let query = TileQuery {
+                platform: matches.value_of("platform")
+                    .map(|x| x.to_string()),
+                min_timestamp: match matches.value_of("min_timestamp") {
+                    Some(value) => Some(value.parse::<i64>()?),
+                    None => None,
+                },
+                max_timestamp: match matches.value_of("max_timestamp") {
+                    Some(value) => Some(value.parse::<i64>()?),
+                    None => None,
+                },
+                ..Default::default()
+            };
+
+            let geocode = match matches.value_of("geocode") {
+                Some("quadtile") => Geocode::QuadTile,
+                _ => Geocode::Geohash,
+            };
+
+            Source::Store(TileStore::open(directory)?, query, geocode)
+        },
+        false => Source::Directory(directory.to_path_buf()),
+    };
+
+    // parse render options
+    let bands = matches.value_of("bands").unwrap().split(',')
+        .map(|x| x.trim().parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    let stretch = matches.value_of("stretch").unwrap().split(',')
+        .map(|x| x.trim().parse::<f64>())
+        .collect::<Result<Vec<f64>, _>>()?;
+    if stretch.len() != 2 {
+        return Err("stretch must be formatted as 'min,max'".into());
+    }
+
+    let tile_size = matches.value_of("tile_size")
+        .unwrap().parse::<usize>()?;
+    let options = RenderOptions {
+        bands,
+        stretch: Stretch::Linear(stretch[0], stretch[1]),
+        ..Default::default()
+    };
+
+    // handle requests sequentially
+    let listener = TcpListener::bind(matches.value_of("address").unwrap())?;
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("failed to accept connection: {}", e);
+                continue;
+            },
+        };
+
+        if let Err(e) = handle(&mut stream, &source, tile_size, &options) {
+            eprintln!("failed to handle request: {}", e);
+            let _ = respond(&mut stream, "500 Internal Server Error",
+                "text/plain", e.to_string().as_bytes());
+        }
+    }
+
+    Ok(())
+}
+
+fn handle(stream: &mut TcpStream, source: &Source, tile_size: usize,
+        options: &RenderOptions) -> Result<(), Box<dyn Error>> {
+    // read request line and discard headers
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let fields: Vec<&str> = request_line.split_whitespace().collect();
+    if fields.len() < 2 || fields[0] != "GET" {
+        return respond(stream, "405 Method Not Allowed",
+            "text/plain", b"method not allowed");
+    }
+
+    // parse tile request and retrieve dataset
+    let (code, format) = match parse_path(fields[1]) {
+        Some(tile) => tile,
+        None => return respond(stream, "404 Not Found",
+            "text/plain", b"not found"),
+    };
+
+    let dataset = match source.get_dataset(&code, tile_size)? {
+        Some(dataset) => dataset,
+        None => return respond(stream, "404 Not Found",
+            "text/plain", b"not found"),
+    };
+
+    // encode tile in the requested format
+    match format {
+        Format::Image(image_format) => {
+            let data = st_image::render::render_image(&dataset,
+                options, image_format)?;
+            let content_type = match image_format {
+                ImageFormat::Jpeg => "image/jpeg",
+                ImageFormat::Png => "image/png",
+            };
+
+            respond(stream, "200 OK", content_type, &data)
+        },
+        Format::Serialized => {
+            let mut data = Vec::new();
+            st_image::serialize::write(&dataset, &mut data)?;
+            respond(stream, "200 OK", "application/octet-stream", &data)
+        },
+    }
+}
+
+fn parse_path(path: &str) -> Option<(String, Format)> {
+    // strip query string and parse extension
+    let path = path.split('?').next()?.trim_start_matches('/');
+    let (path, format) = if let Some(path) = path.strip_suffix(".png") {
+        (path, Format::Image(ImageFormat::Png))
+    } else if let Some(path) = path.strip_suffix(".jpg") {
+        (path, Format::Image(ImageFormat::Jpeg))
+    } else if let Some(path) = path.strip_suffix(".st") {
+        (path, Format::Serialized)
+    } else {
+        return None;
+    };
+
+    // parse '{z}/{x}/{y}' xyz or '{quadkey}' paths
+    let fields: Vec<&str> = path.split('/').collect();
+    let code = match fields.len() {
+        1 if fields[0].chars().all(|x| x.is_digit(4)) =>
+            fields[0].to_string(),
+        3 => {
+            let zoom = fields[0].parse::<usize>().ok()?;
+            let x = fields[1].parse::<usize>().ok()?;
+            let y = fields[2].parse::<usize>().ok()?;
+            if zoom > 30 || x >= (1 << zoom) || y >= (1 << zoom) {
+                return None;
+            }
+
+            st_image::coordinate::get_quadkey(x, y, zoom)
+        },
+        _ => return None,
+    };
+
+    match code.is_empty() {
+        true => None,
+        false => Some((code, format)),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str,
+        data: &[u8]) -> Result<(), Box<dyn Error>> {
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: {}\r\n\
+        Content-Length: {}\r\nAccess-Control-Allow-Origin: *\r\n\
+        Connection: close\r\n\r\n", status, content_type, data.len())?;
+    stream.write_all(data)?;
+    stream.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Source;
+    use st_image::coordinate::{Geocode, GeoTransform};
+    use st_image::store::{TileQuery, TileStore};
+    use st_image::transform::{Halo, SplitOptions};
+
+    use gdal::Dataset;
+
+    use std::path::{Path, PathBuf};
+
+    struct TempDirectory(PathBuf);
+
+    impl TempDirectory {
+        fn new(name: &str) -> TempDirectory {
+            // unique per process so concurrent test runs do not collide
+            let path = std::env::temp_dir().join(format!(
+                "st-image-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).expect("create directory");
+            TempDirectory(path)
+        }
+    }
+
+    impl Drop for TempDirectory {
+        fn drop(&mut self) {
+            // remove directory even if the test panics
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn get_quadkey(lon: f64, lat: f64, zoom: usize) -> String {
+        // project coordinate to web mercator
+        let (lon, lat) = (lon.to_radians(), lat.to_radians());
+        let (x, y) = (6378137.0 * lon, 6378137.0
+            * (std::f64::consts::FRAC_PI_4 + (lat / 2.0)).tan().ln());
+        Geocode::QuadTile.get_code(x, y, zoom).expect("quadkey")
+    }
+
+    #[test]
+    fn server_get_dataset() {
+        // name fixture by the quadkey of a tile within its bounds
+        let code = get_quadkey(-105.5, 44.5, 8);
+
+        let directory = TempDirectory::new("server-tiles");
+        std::fs::copy(Path::new("fixtures/MCD43A4.h10v04.006.tif"),
+            directory.0.join(format!("{}.tif", code)))
+            .expect("copy fixture");
+
+        // tiles are warped onto the quadtile window
+        let source = Source::Directory(directory.0.clone());
+        let dataset = source.get_dataset(&code, 64)
+            .expect("get dataset").expect("dataset");
+        assert_eq!(dataset.raster_size(), (64, 64));
+
+        let (min_x, max_x, min_y, max_y) =
+            Geocode::QuadTile.get_window(&code).expect("window");
+        let transform = GeoTransform::from(
+            dataset.geo_transform().expect("transform"));
+        let (origin_x, origin_y) = transform.origin();
+        let pixel_size = (max_x - min_x) / 64.0;
+
+        assert!((origin_x - min_x).abs() < 1e-6);
+        assert!((origin_y - max_y).abs() < 1e-6);
+        assert!((transform.pixel_width() - pixel_size).abs() < 1e-6);
+        assert!((transform.pixel_height() + pixel_size).abs() < 1e-6);
+        assert!(((max_y - min_y) / 64.0 - pixel_size).abs() < 1e-6);
+
+        // missing tiles are not found
+        assert!(source.get_dataset("0", 64).expect("get dataset").is_none());
+    }
+
+    #[test]
+    fn server_get_store_dataset() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // insert adjacent geohash tiles into a store
+        let directory = TempDirectory::new("server-store");
+        let mut store = TileStore::open(&directory.0).expect("open store");
+
+        let options = SplitOptions {
+            halo: Some(Halo::Pixels(4)),
+            ..Default::default()
+        };
+
+        for x in [-105.9, -104.6].iter() {
+            let code = Geocode::Geohash.get_code(*x, 44.25, 3)
+                .expect("geocode code");
+            let (min_x, max_x, min_y, max_y) = Geocode::Geohash
+                .get_window(&code).expect("geocode window");
+            let tile = st_image::transform::split_tile(&dataset, min_x,
+                max_x, min_y, max_y, 4326, &options)
+                .expect("split tile").expect("tile");
+
+            store.insert("modis", &code, 0, &tile).expect("insert tile");
+        }
+
+        // store tiles are mosaicked and warped onto the quadtile window
+        let source = Source::Store(store, TileQuery::default(),
+            Geocode::Geohash);
+        let code = get_quadkey(-105.0, 44.25, 8);
+        let dataset = source.get_dataset(&code, 64)
+            .expect("get dataset").expect("dataset");
+        assert_eq!(dataset.raster_size(), (64, 64));
+        assert!(st_image::get_coverage(&dataset).expect("coverage") > 0.0);
+
+        // quadtiles outside stored windows are not found
+        let code = get_quadkey(-80.0, 44.25, 8);
+        assert!(source.get_dataset(&code, 64)
+            .expect("get dataset").is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use gdal::{Dataset, Driver};
+use gdal::raster::GdalType;
+use gdal::spatial_ref::CoordTransform;
+use gdal_sys::GDALDataType;
+
+use crate::FromPrimitive;
+use crate::coordinate::GeoTransform;
+use crate::geometry::Geometry;
+
+use std::error::Error;
+
+pub fn clip(dataset: &Dataset, geometry: &str, epsg_code: u32)
+        -> Result<Option<Dataset>, Box<dyn Error>> {
+    let geometry = Geometry::parse(geometry)?;
+    clip_geometry(dataset, &geometry, epsg_code)
+}
+
+pub fn clip_geometry(dataset: &Dataset, geometry: &Geometry,
+        epsg_code: u32) -> Result<Option<Dataset>, Box<dyn Error>> {
+    // convert geometry into dataset pixel space
+    let (transform, projection, src_spatial_ref, dst_spatial_ref) =
+        crate::coordinate::get_transform_refs(dataset, epsg_code)?;
+    let reverse_transform = CoordTransform::new(
+        &dst_spatial_ref, &src_spatial_ref)?;
+
+    let pixel_geometry = geometry.transform(&reverse_transform)?
+        .to_pixels(&transform)?;
+
+    // crop to geometry bounding box within image
+    let (min_x, max_x, min_y, max_y) = pixel_geometry.bounds();
+    let (width, height) = dataset.raster_size();
+
+    let min_px = (min_x.floor() as isize).max(0);
+    let max_px = (max_x.ceil() as isize).min(width as isize);
+    let min_py = (min_y.floor() as isize).max(0);
+    let max_py = (max_y.ceil() as isize).min(height as isize);
+
+    if min_px >= max_px || min_py >= max_py {
+        return Ok(None);
+    }
+
+    let offset = (min_px, min_py);
+    let size = ((max_px - min_px) as usize, (max_py - min_py) as usize);
+
+    // translate transform origin to window
+    let clip_transform = transform.offset(min_px as f64, min_py as f64);
+
+    match dataset.rasterband(1)?.band_type() {
+        GDALDataType::GDT_Byte => _clip::<u8>(dataset, &pixel_geometry,
+            offset, size, &clip_transform, &projection),
+        GDALDataType::GDT_Int16 => _clip::<i16>(dataset, &pixel_geometry,
+            offset, size, &clip_transform, &projection),
+        GDALDataType::GDT_UInt16 => _clip::<u16>(dataset, &pixel_geometry,
+            offset, size, &clip_transform, &projection),
+        GDALDataType::GDT_Float32 => _clip::<f32>(dataset, &pixel_geometry,
+            offset, size, &clip_transform, &projection),
+        band_type => Err(format!(
+            "unsupported band type '{}'", band_type).into()),
+    }
+}
+
+fn _clip<T: Copy + FromPrimitive + GdalType>(dataset: &Dataset,
+        pixel_geometry: &Geometry, offset: (isize, isize),
+        size: (usize, usize), transform: &GeoTransform, projection: &str)
+        -> Result<Option<Dataset>, Box<dyn Error>> {
+    let mask = pixel_geometry.get_mask(offset, size);
+    if !mask.iter().any(|x| *x) {
+        return Ok(None);
+    }
+
+    // masked pixels require a no_data value
+    let no_data_value = dataset.rasterband(1)?
+        .no_data_value().unwrap_or(0.0);
+
+    // initialize clip Dataset
+    let driver = Driver::get("Mem")?;
+    let clip_dataset = crate::init_dataset(&driver, "unreachable",
+        T::gdal_type(), size.0 as isize, size.1 as isize,
+        dataset.raster_count(), Some(no_data_value))?;
+
+    clip_dataset.set_geo_transform(&<[f64; 6]>::from(*transform))?;
+    clip_dataset.set_projection(projection)?;
+
+    // copy rasterband windows masking pixels outside geometry
+    for i in 0..dataset.raster_count() {
+        let index = i + 1;
+        let rasterband = dataset.rasterband(index)?;
+        let band_no_data_value =
+            rasterband.no_data_value().unwrap_or(no_data_value);
+
+        let mut buffer = rasterband.read_as::<T>(offset, size, size)?;
+        for (pixel, inside) in buffer.data.iter_mut().zip(mask.iter()) {
+            if !*inside {
+                *pixel = T::from_f64(band_no_data_value);
+            }
+        }
+
+        let clip_rasterband = clip_dataset.rasterband(index)?;
+        clip_rasterband.write::<T>((0, 0), size, &buffer)?;
+        clip_rasterband.set_no_data_value(band_no_data_value)?;
+    }
+
+    Ok(Some(clip_dataset))
+}
+
+#[cfg(test)]
+mod tests {
+    use gdal::Dataset;
+
+    use std::path::Path;
+
+    #[test]
+    fn clip_polygon() {
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // clip to a geographic polygon within the image
+        let clip_dataset = super::clip(&dataset, "POLYGON ((-106 44, \
+            -104 44, -104 45, -106 45, -106 44))", 4326)
+            .expect("clip dataset").expect("clip within image");
+
+        let (width, height) = dataset.raster_size();
+        let (clip_width, clip_height) = clip_dataset.raster_size();
+        assert!(clip_width < width && clip_height < height);
+        assert_eq!(clip_dataset.raster_count(), dataset.raster_count());
+
+        // bounding box corners fall outside the sinusoidal polygon
+        let coverage = crate::get_coverage(&clip_dataset)
+            .expect("clip coverage");
+        assert!(coverage > 0.0 && coverage < 1.0);
+
+        // polygons outside the image produce no dataset
+        assert!(super::clip(&dataset, "{\"type\": \"Polygon\", \
+            \"coordinates\": [[[0, 0], [1, 0], [1, 1], [0, 1], [0, 0]]]}",
+            4326).expect("clip dataset").is_none());
+    }
+}
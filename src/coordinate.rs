@@ -1,41 +1,445 @@
+#[cfg(feature = "gdal-backend")]
 use gdal::Dataset;
+#[cfg(feature = "gdal-backend")]
 use gdal::spatial_ref::{CoordTransform, SpatialRef};
 
+use crate::footprint::Footprint;
+
 use std::error::Error;
+#[cfg(feature = "gdal-backend")]
 use std::ffi::CStr;
 
 pub type WindowBounds = (Vec<f64>, Vec<f64>, Vec<f64>);
 
+pub const DENSIFY_POINTS: usize = 20;
+
+const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+const MERCATOR_BOUND: f64 = 20037508.342789244;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Geocode {
+    Geohash,
+    QuadTile,
+}
+
+impl Geocode {
+    pub fn get_code(&self, x: f64, y: f64, precision: usize)
+            -> Result<String, Box<dyn Error>> {
+        match self {
+            Geocode::Geohash => {
+                if x < -180.0 || x > 180.0 || y < -90.0 || y > 90.0 {
+                    return Err("coordinate out of geohash bounds".into());
+                }
+
+                // interleave longitude and latitude bits
+                let (mut min_x, mut max_x) = (-180.0, 180.0);
+                let (mut min_y, mut max_y) = (-90.0, 90.0);
+
+                let mut code = String::new();
+                let (mut bits, mut bit_count) = (0usize, 0);
+                let mut even = true;
+                while code.len() < precision {
+                    let (value, min, max) = match even {
+                        true => (x, &mut min_x, &mut max_x),
+                        false => (y, &mut min_y, &mut max_y),
+                    };
+
+                    let mid = (*min + *max) / 2.0;
+                    bits <<= 1;
+                    if value >= mid {
+                        bits |= 1;
+                        *min = mid;
+                    } else {
+                        *max = mid;
+                    }
+
+                    even = !even;
+                    bit_count += 1;
+
+                    // append character every five bits
+                    if bit_count == 5 {
+                        code.push(GEOHASH_ALPHABET[bits] as char);
+                        bits = 0;
+                        bit_count = 0;
+                    }
+                }
+
+                Ok(code)
+            },
+            Geocode::QuadTile => {
+                if x < -MERCATOR_BOUND || x > MERCATOR_BOUND
+                        || y < -MERCATOR_BOUND || y > MERCATOR_BOUND {
+                    return Err("coordinate out of quadtile bounds".into());
+                }
+
+                // compute tile indices
+                let (interval, _) = self.get_intervals(precision);
+                let max_index = (1usize << precision) - 1;
+
+                let tile_x = (((x + MERCATOR_BOUND) / interval)
+                    .floor() as usize).min(max_index);
+                let tile_y = (((MERCATOR_BOUND - y) / interval)
+                    .floor() as usize).min(max_index);
+
+                // compute quadkey
+                let mut code = String::new();
+                for i in (0..precision).rev() {
+                    let mask = 1 << i;
+                    let mut digit = 0;
+                    if tile_x & mask != 0 {
+                        digit += 1;
+                    }
+
+                    if tile_y & mask != 0 {
+                        digit += 2;
+                    }
+
+                    code.push_str(&digit.to_string());
+                }
+
+                Ok(code)
+            },
+        }
+    }
+
+    pub fn get_window(&self, code: &str)
+            -> Result<(f64, f64, f64, f64), Box<dyn Error>> {
+        match self {
+            Geocode::Geohash => {
+                // narrow longitude and latitude ranges by interleaved bits
+                let (mut min_x, mut max_x) = (-180.0, 180.0);
+                let (mut min_y, mut max_y) = (-90.0, 90.0);
+
+                let mut even = true;
+                for c in code.bytes() {
+                    let bits = match GEOHASH_ALPHABET.iter()
+                            .position(|x| *x == c) {
+                        Some(bits) => bits,
+                        None => return Err(format!(
+                            "invalid geohash code '{}'", code).into()),
+                    };
+
+                    for i in (0..5).rev() {
+                        let (min, max) = match even {
+                            true => (&mut min_x, &mut max_x),
+                            false => (&mut min_y, &mut max_y),
+                        };
+
+                        let mid = (*min + *max) / 2.0;
+                        match bits & (1 << i) != 0 {
+                            true => *min = mid,
+                            false => *max = mid,
+                        }
+
+                        even = !even;
+                    }
+                }
+
+                Ok((min_x, max_x, min_y, max_y))
+            },
+            Geocode::QuadTile => {
+                // compute tile indices from quadkey digits
+                let (mut tile_x, mut tile_y) = (0usize, 0usize);
+                for c in code.chars() {
+                    let digit = match c.to_digit(4) {
+                        Some(digit) => digit as usize,
+                        None => return Err(format!(
+                            "invalid quadtile code '{}'", code).into()),
+                    };
+
+                    tile_x = (tile_x << 1) | (digit & 1);
+                    tile_y = (tile_y << 1) | (digit >> 1);
+                }
+
+                let (interval, _) = self.get_intervals(code.len());
+                let min_x = -MERCATOR_BOUND + (tile_x as f64 * interval);
+                let max_y = MERCATOR_BOUND - (tile_y as f64 * interval);
+
+                Ok((min_x, min_x + interval, max_y - interval, max_y))
+            },
+        }
+    }
+
+    pub fn get_epsg_code(&self) -> u32 {
+        match self {
+            Geocode::Geohash => 4326,
+            Geocode::QuadTile => 3857,
+        }
+    }
+
+    pub fn get_intervals(&self, precision: usize) -> (f64, f64) {
+        match self {
+            Geocode::Geohash => {
+                // longitude receives the extra bit on odd precisions
+                let bits = precision * 5;
+                let x_bits = (bits + 1) / 2;
+                let y_bits = bits / 2;
+
+                (360.0 / 2f64.powi(x_bits as i32),
+                    180.0 / 2f64.powi(y_bits as i32))
+            },
+            Geocode::QuadTile => {
+                let interval = (MERCATOR_BOUND * 2.0)
+                    / 2f64.powi(precision as i32);
+
+                (interval, interval)
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeoTransform {
+    transform: [f64; 6],
+}
+
+impl GeoTransform {
+    pub const fn new(origin_x: f64, pixel_width: f64, row_rotation: f64,
+            origin_y: f64, column_rotation: f64, pixel_height: f64)
+            -> GeoTransform {
+        GeoTransform { transform: [origin_x, pixel_width, row_rotation,
+            origin_y, column_rotation, pixel_height] }
+    }
+
+    pub fn invert(&self) -> Result<GeoTransform, Box<dyn Error>> {
+        let transform = &self.transform;
+
+        // compute determinant of the affine matrix, checking it relative
+        //   to its terms so small pixel sizes remain invertible
+        let (scale, shear) = (transform[1] * transform[5],
+            transform[2] * transform[4]);
+        let determinant = scale - shear;
+        if determinant.abs() <= f64::EPSILON * scale.abs().max(shear.abs()) {
+            return Err("transform is not invertible".into());
+        }
+
+        // invert the affine matrix and translate origin
+        let inverse_determinant = 1.0 / determinant;
+        let x_x = transform[5] * inverse_determinant;
+        let x_y = -transform[2] * inverse_determinant;
+        let y_x = -transform[4] * inverse_determinant;
+        let y_y = transform[1] * inverse_determinant;
+
+        Ok(GeoTransform::new(
+            -(transform[0] * x_x) - (transform[3] * x_y),
+            x_x,
+            x_y,
+            -(transform[0] * y_x) - (transform[3] * y_y),
+            y_x,
+            y_y,
+        ))
+    }
+
+    pub fn is_rotated(&self) -> bool {
+        self.transform[2] != 0.0 || self.transform[4] != 0.0
+    }
+
+    pub fn offset(&self, x: f64, y: f64) -> GeoTransform {
+        // translate origin to the pixel window corner
+        let (origin_x, origin_y) = self.pixel_to_world(x, y);
+
+        let mut transform = self.transform;
+        transform[0] = origin_x;
+        transform[3] = origin_y;
+        GeoTransform { transform }
+    }
+
+    pub fn origin(&self) -> (f64, f64) {
+        (self.transform[0], self.transform[3])
+    }
+
+    pub fn pixel_height(&self) -> f64 {
+        self.transform[5]
+    }
+
+    pub fn pixel_to_world(&self, x: f64, y: f64) -> (f64, f64) {
+        let transform = &self.transform;
+        (transform[0] + (x * transform[1]) + (y * transform[2]),
+            transform[3] + (x * transform[4]) + (y * transform[5]))
+    }
+
+    pub fn pixel_width(&self) -> f64 {
+        self.transform[1]
+    }
+
+    pub fn scale(&self, x_scale: f64, y_scale: f64) -> GeoTransform {
+        // scale pixel dimensions maintaining the origin
+        let transform = &self.transform;
+        GeoTransform::new(transform[0], transform[1] * x_scale,
+            transform[2] * y_scale, transform[3],
+            transform[4] * x_scale, transform[5] * y_scale)
+    }
+
+    pub fn world_to_pixel(&self, x: f64, y: f64)
+            -> Result<(f64, f64), Box<dyn Error>> {
+        Ok(self.invert()?.pixel_to_world(x, y))
+    }
+}
+
+impl From<[f64; 6]> for GeoTransform {
+    fn from(transform: [f64; 6]) -> GeoTransform {
+        GeoTransform { transform }
+    }
+}
+
+impl From<GeoTransform> for [f64; 6] {
+    fn from(geo_transform: GeoTransform) -> [f64; 6] {
+        geo_transform.transform
+    }
+}
+
+pub fn get_quadkey(x: usize, y: usize, zoom: usize) -> String {
+    // interleave xyz tile index bits into quadkey digits
+    let mut code = String::new();
+    for i in (0..zoom).rev() {
+        let digit = ((x >> i) & 1) + (((y >> i) & 1) << 1);
+        code.push_str(&digit.to_string());
+    }
+
+    code
+}
+
+pub fn densify_bounds(min_x: f64, max_x: f64, min_y: f64, max_y: f64,
+        points: usize) -> (Vec<f64>, Vec<f64>) {
+    let points = points.max(1);
+    let mut xs = Vec::with_capacity(points * 4);
+    let mut ys = Vec::with_capacity(points * 4);
+
+    // walk boundary counter-clockwise from minimum corner
+    let corners = [(min_x, min_y), (max_x, min_y),
+        (max_x, max_y), (min_x, max_y)];
+    for i in 0..corners.len() {
+        let (start_x, start_y) = corners[i];
+        let (end_x, end_y) = corners[(i + 1) % corners.len()];
+
+        for j in 0..points {
+            let ratio = j as f64 / points as f64;
+            xs.push(start_x + ((end_x - start_x) * ratio));
+            ys.push(start_y + ((end_y - start_y) * ratio));
+        }
+    }
+
+    (xs, ys)
+}
+
+#[cfg(feature = "gdal-backend")]
 pub fn get_bounds(dataset: &Dataset, epsg_code: u32)
         -> Result<(f64, f64, f64, f64), Box<dyn Error>> {
+    get_bounds_densified(dataset, epsg_code, DENSIFY_POINTS, false)
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn get_bounds_densified(dataset: &Dataset, epsg_code: u32,
+        points: usize, wrap_antimeridian: bool)
+        -> Result<(f64, f64, f64, f64), Box<dyn Error>> {
     // initialize transform array and CoordTransform's from dataset
     let (transform, _, src_spatial_ref, dst_spatial_ref) =
         get_transform_refs(dataset, epsg_code)?;
     let coord_transform = CoordTransform::new(
         &src_spatial_ref, &dst_spatial_ref)?;
 
-    // compute minimum and maximum x and y coordinates
+    // sample points along each image edge
     let (src_width, src_height) = dataset.raster_size();
-    let corner_pixels = vec![
-        (0, 0, 0),
-        (src_width as isize, 0, 0),
-        (0, src_height as isize, 0),
-        (src_width as isize, src_height as isize, 0)
-    ];
+    let (pixel_xs, pixel_ys) = densify_bounds(0.0, src_width as f64,
+        0.0, src_height as f64, points);
+
+    let (xs, ys): (Vec<f64>, Vec<f64>) = pixel_xs.iter()
+        .zip(pixel_ys.iter())
+        .map(|(x, y)| transform.pixel_to_world(*x, *y)).unzip();
+
+    // points near poles may fail to transform
+    let (xs, ys) = transform_valid_coords(&xs, &ys, &coord_transform)?;
 
-    let (xs, ys, _) = transform_pixels(&corner_pixels,
-        &transform, &coord_transform)?;
+    // compute minimum and maximum x and y coordinates
+    let wrap_antimeridian = wrap_antimeridian
+        && is_geographic(&dst_spatial_ref)?;
+    Ok(get_ring_bounds(&xs, &ys, wrap_antimeridian))
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn transform_bounds(min_x: f64, max_x: f64, min_y: f64, max_y: f64,
+        src_epsg_code: u32, dst_epsg_code: u32)
+        -> Result<(f64, f64, f64, f64), Box<dyn Error>> {
+    if src_epsg_code == dst_epsg_code {
+        return Ok((min_x, max_x, min_y, max_y));
+    }
+
+    let coord_transform =
+        get_coord_transform(src_epsg_code, dst_epsg_code)?;
 
+    // sample points along each bounds edge
+    let (xs, ys) = densify_bounds(min_x, max_x,
+        min_y, max_y, DENSIFY_POINTS);
+    let (xs, ys) = transform_valid_coords(&xs, &ys, &coord_transform)?;
+
+    Ok(get_ring_bounds(&xs, &ys, false))
+}
+
+#[cfg(feature = "gdal-backend")]
+fn get_ring_bounds(xs: &[f64], ys: &[f64], wrap_antimeridian: bool)
+        -> (f64, f64, f64, f64) {
     let min_cx = xs.iter().cloned().fold(1./0., f64::min);
     let max_cx = xs.iter().cloned().fold(f64::NAN, f64::max);
     let min_cy = ys.iter().cloned().fold(1./0., f64::min);
     let max_cy = ys.iter().cloned().fold(f64::NAN, f64::max);
 
-    Ok((min_cx, max_cx, min_cy, max_cy))
+    if !wrap_antimeridian {
+        return (min_cx, max_cx, min_cy, max_cy);
+    }
+
+    // count longitude jumps along the ring
+    let crossings = (0..xs.len())
+        .filter(|i| (xs[(i + 1) % xs.len()] - xs[*i]).abs() > 180.0)
+        .count();
+
+    if crossings == 0 {
+        (min_cx, max_cx, min_cy, max_cy)
+    } else if crossings % 2 == 1 {
+        // ring encloses a pole -> extend to cover all longitudes
+        let mean_cy = ys.iter().sum::<f64>() / ys.len() as f64;
+        match mean_cy >= 0.0 {
+            true => (-180.0, 180.0, min_cy, 90.0),
+            false => (-180.0, 180.0, -90.0, max_cy),
+        }
+    } else {
+        // ring crosses antimeridian -> minimum x exceeds maximum x
+        let shifted_xs: Vec<f64> = xs.iter()
+            .map(|x| if *x < 0.0 { x + 360.0 } else { *x }).collect();
+
+        let min_cx = shifted_xs.iter().cloned().fold(1./0., f64::min);
+        let max_cx = shifted_xs.iter().cloned().fold(f64::NAN, f64::max);
+
+        match max_cx > 180.0 {
+            true => (min_cx, max_cx - 360.0, min_cy, max_cy),
+            false => (min_cx, max_cx, min_cy, max_cy),
+        }
+    }
 }
 
+#[cfg(feature = "gdal-backend")]
+pub fn get_coord_transform(src_epsg_code: u32, dst_epsg_code: u32)
+        -> Result<CoordTransform, Box<dyn Error>> {
+    let src_spatial_ref = SpatialRef::from_epsg(src_epsg_code)?;
+    let dst_spatial_ref = SpatialRef::from_epsg(dst_epsg_code)?;
+
+    #[cfg(major_ge_3)]
+    {
+        use gdal_sys::OSRAxisMappingStrategy;
+        src_spatial_ref.set_axis_mapping_strategy(
+            OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+        dst_spatial_ref.set_axis_mapping_strategy(
+            OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+    }
+
+    let coord_transform = CoordTransform::new(
+        &src_spatial_ref, &dst_spatial_ref)?;
+    Ok(coord_transform)
+}
+
+#[cfg(feature = "gdal-backend")]
 pub fn get_transform_refs(dataset: &Dataset, epsg_code: u32) 
-        -> Result<([f64; 6], String, SpatialRef, SpatialRef), Box<dyn Error>> {
+        -> Result<(GeoTransform, String, SpatialRef, SpatialRef),
+            Box<dyn Error>> {
     // identify transform array and projection from dataset
     let (transform, projection) = match dataset.geo_transform() {
         Ok(transform) => (transform, dataset.projection()),
@@ -83,11 +487,36 @@ pub fn get_transform_refs(dataset: &Dataset, epsg_code: u32)
             OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
     }
 
-    Ok((transform, projection, src_spatial_ref, dst_spatial_ref))
+    Ok((transform.into(), projection, src_spatial_ref, dst_spatial_ref))
+}
+
+pub fn get_footprint_windows(footprint: &Footprint, x_interval: f64,
+        y_interval: f64) -> Vec<(f64, f64, f64, f64)> {
+    // compute windows over footprint bounds
+    let (min_x, max_x, min_y, max_y) = footprint.bounds();
+    let window_bounds = get_windows(min_x, max_x,
+        min_y, max_y, x_interval, y_interval);
+
+    // filter windows which do not intersect footprint
+    window_bounds.into_iter()
+        .filter(|(min_x, max_x, min_y, max_y)|
+            footprint.intersects(*min_x, *max_x, *min_y, *max_y))
+        .collect()
 }
 
 pub fn get_windows(min_x: f64, max_x: f64, min_y: f64, max_y: f64,
         x_interval: f64, y_interval: f64) -> Vec<(f64, f64, f64, f64)> {
+    // if bounds cross the antimeridian -> wrap windows back into range
+    if min_x > max_x {
+        return get_windows(min_x, max_x + 360.0, min_y, max_y,
+                x_interval, y_interval).into_iter()
+            .map(|(x_min, x_max, y_min, y_max)| match x_min >= 180.0 {
+                true => (x_min - 360.0, x_max - 360.0, y_min, y_max),
+                false => (x_min, x_max, y_min, y_max),
+            })
+            .collect();
+    }
+
     // compute indices for minimum and maximum coordinates
     let min_x_index = (min_x / x_interval).floor() as i32;
     let max_x_index = (max_x / x_interval).ceil() as i32;
@@ -119,30 +548,73 @@ pub fn get_windows(min_x: f64, max_x: f64, min_y: f64, max_y: f64,
     window_bounds
 }
 
+#[cfg(feature = "gdal-backend")]
+pub fn is_geographic(spatial_ref: &SpatialRef)
+        -> Result<bool, Box<dyn Error>> {
+    let rv = unsafe {
+        gdal_sys::OSRIsGeographic(spatial_ref.to_c_hsrs())
+    };
+
+    Ok(rv != 0)
+}
+
+pub fn invert_coords(xs: &[f64], ys: &[f64], transform: &GeoTransform)
+        -> Result<(Vec<f64>, Vec<f64>), Box<dyn Error>> {
+    // the inverse transform maps world coordinates to pixels
+    let inverse = transform.invert()?;
+    Ok(xs.iter().zip(ys.iter())
+        .map(|(x, y)| inverse.pixel_to_world(*x, *y)).unzip())
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn coord_to_pixel(x: f64, y: f64, transform: &GeoTransform,
+        coord_transform: &CoordTransform)
+        -> Result<(f64, f64), Box<dyn Error>> {
+    let (x, y, _) = transform_coord(x, y, 0.0, coord_transform)?;
+    let (pixel_xs, pixel_ys) = invert_coords(&[x], &[y], transform)?;
+
+    Ok((pixel_xs[0], pixel_ys[0]))
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn coords_to_pixels(xs: &[f64], ys: &[f64], transform: &GeoTransform,
+        coord_transform: &CoordTransform)
+        -> Result<Vec<Option<(f64, f64)>>, Box<dyn Error>> {
+    let inverse = transform.invert()?;
+
+    // attempt to transform all coordinates at once
+    let (mut dst_xs, mut dst_ys) = (xs.to_vec(), ys.to_vec());
+    let mut zs = vec![0.0; xs.len()];
+    if coord_transform.transform_coords(&mut dst_xs,
+            &mut dst_ys, &mut zs).is_ok() {
+        return Ok(dst_xs.iter().zip(dst_ys.iter())
+            .map(|(x, y)| Some(inverse.pixel_to_world(*x, *y))).collect());
+    }
+
+    // coordinates failing to transform individually have no pixel
+    Ok(xs.iter().zip(ys.iter())
+        .map(|(x, y)| transform_coord(*x, *y, 0.0, coord_transform).ok()
+            .map(|(x, y, _)| inverse.pixel_to_world(x, y)))
+        .collect())
+}
+
+#[cfg(feature = "gdal-backend")]
 pub fn transform_pixel(x: isize, y: isize, z: isize,
-        transform: &[f64; 6], coord_transform: &CoordTransform)
+        transform: &GeoTransform, coord_transform: &CoordTransform)
         -> Result<(f64, f64, f64), Box<dyn Error>> {
-    let x_coord = transform[0] + (x as f64 * transform[1])
-        + (y as f64 * transform[2]);
-    let y_coord = transform[3] + (x as f64 * transform[4])
-        + (y as f64 * transform[5]);
+    let (x_coord, y_coord) = transform.pixel_to_world(x as f64, y as f64);
 
     transform_coord(x_coord, y_coord, z as f64, coord_transform)
 }
 
+#[cfg(feature = "gdal-backend")]
 pub fn transform_pixels(pixels: &[(isize, isize, isize)],
-        transform: &[f64; 6], coord_transform: &CoordTransform)
+        transform: &GeoTransform, coord_transform: &CoordTransform)
         -> Result<WindowBounds, Box<dyn Error>> {
     // convert pixels to coordinates
-    let mut xs: Vec<f64> = pixels.iter().map(|(x, y, _)| {
-        transform[0] + (*x as f64 * transform[1])
-            + (*y as f64 * transform[2])
-    }).collect();
-
-    let mut ys: Vec<f64> = pixels.iter().map(|(x, y, _)| {
-        transform[3] + (*x as f64 * transform[4])
-            + (*y as f64 * transform[5])
-    }).collect();
+    let (mut xs, mut ys): (Vec<f64>, Vec<f64>) = pixels.iter()
+        .map(|(x, y, _)| transform.pixel_to_world(*x as f64, *y as f64))
+        .unzip();
 
     let mut zs: Vec<f64> = pixels.iter()
         .map(|(_, _, z)| *z as f64).collect();
@@ -153,6 +625,7 @@ pub fn transform_pixels(pixels: &[(isize, isize, isize)],
     Ok((xs, ys, zs))
 }
 
+#[cfg(feature = "gdal-backend")]
 pub fn transform_coord(x: f64, y: f64, z: f64,
         coord_transform: &CoordTransform)
         -> Result<(f64, f64, f64), Box<dyn Error>> {
@@ -168,8 +641,40 @@ pub fn transform_coord(x: f64, y: f64, z: f64,
     Ok((xs[0], ys[0], zs[0]))
 }
 
+#[cfg(feature = "gdal-backend")]
+pub fn transform_valid_coords(xs: &[f64], ys: &[f64],
+        coord_transform: &CoordTransform)
+        -> Result<(Vec<f64>, Vec<f64>), Box<dyn Error>> {
+    // attempt to transform all coordinates at once
+    let (mut dst_xs, mut dst_ys) = (xs.to_vec(), ys.to_vec());
+    let mut zs = vec![0.0; xs.len()];
+    if coord_transform.transform_coords(&mut dst_xs,
+            &mut dst_ys, &mut zs).is_ok() {
+        return Ok((dst_xs, dst_ys));
+    }
+
+    // transform coordinates individually dropping failures
+    let (mut dst_xs, mut dst_ys) = (Vec::new(), Vec::new());
+    for (x, y) in xs.iter().zip(ys.iter()) {
+        if let Ok((dst_x, dst_y, _)) =
+                transform_coord(*x, *y, 0.0, coord_transform) {
+            dst_xs.push(dst_x);
+            dst_ys.push(dst_y);
+        }
+    }
+
+    if dst_xs.is_empty() {
+        return Err("failed to transform coordinates".into());
+    }
+
+    Ok((dst_xs, dst_ys))
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{Geocode, GeoTransform};
+
+    #[cfg(feature = "gdal-backend")]
     use gdal::spatial_ref::{CoordTransform, SpatialRef};
 
     const APPLETON_LAT_LONG: (f64, f64) = (-88.4, 44.266667);
@@ -177,6 +682,205 @@ mod tests {
     const FORT_COLLINS_LAT_LONG: (f64, f64) = (-105.078056, 40.559167);
     const FORT_COLLINS_MERCATOR: (f64, f64) = (-11697235.69, 4947534.74);
 
+    #[test]
+    fn geocode_get_code() {
+        // test geohash codes
+        let code = Geocode::Geohash.get_code(APPLETON_LAT_LONG.0,
+            APPLETON_LAT_LONG.1, 6).expect("geohash code");
+        assert_eq!(code, "dpc5u6");
+
+        let code = Geocode::Geohash.get_code(FORT_COLLINS_LAT_LONG.0,
+            FORT_COLLINS_LAT_LONG.1, 6).expect("geohash code");
+        assert_eq!(code, "9xjq8z");
+
+        // test quadtile codes
+        let code = Geocode::QuadTile.get_code(APPLETON_MERCATOR.0,
+            APPLETON_MERCATOR.1, 8).expect("quadtile code");
+        assert_eq!(code, "03022201");
+
+        let code = Geocode::QuadTile.get_code(FORT_COLLINS_MERCATOR.0,
+            FORT_COLLINS_MERCATOR.1, 8).expect("quadtile code");
+        assert_eq!(code, "02310101");
+    }
+
+    #[test]
+    fn geocode_get_window() {
+        // validate windows contain the coordinates they encode
+        for (geocode, (x, y), precision) in [
+                (Geocode::Geohash, APPLETON_LAT_LONG, 6),
+                (Geocode::QuadTile, FORT_COLLINS_MERCATOR, 8)].iter() {
+            let code = geocode.get_code(*x, *y, *precision)
+                .expect("geocode code");
+            let (min_x, max_x, min_y, max_y) = geocode.get_window(&code)
+                .expect("geocode window");
+
+            let (x_interval, y_interval) =
+                geocode.get_intervals(*precision);
+            assert!(*x >= min_x && *x < max_x);
+            assert!(*y >= min_y && *y < max_y);
+            assert!((max_x - min_x - x_interval).abs() < 0.000001);
+            assert!((max_y - min_y - y_interval).abs() < 0.000001);
+        }
+
+        assert!(Geocode::QuadTile.get_window("0124").is_err());
+
+        // xyz tile indices
+        assert_eq!(super::get_quadkey(3, 5, 3), "213");
+        assert_eq!(Geocode::QuadTile.get_window("0").expect("window"),
+            (-super::MERCATOR_BOUND, 0.0, 0.0, super::MERCATOR_BOUND));
+    }
+
+    #[test]
+    fn densify_bounds() {
+        let (xs, ys) = super::densify_bounds(0.0, 4.0, 0.0, 2.0, 4);
+        assert_eq!(xs.len(), 16);
+        assert_eq!(ys.len(), 16);
+
+        // validate corners are included
+        assert_eq!((xs[0], ys[0]), (0.0, 0.0));
+        assert_eq!((xs[4], ys[4]), (4.0, 0.0));
+        assert_eq!((xs[8], ys[8]), (4.0, 2.0));
+        assert_eq!((xs[12], ys[12]), (0.0, 2.0));
+
+        // validate points fall on the boundary
+        for (x, y) in xs.iter().zip(ys.iter()) {
+            assert!(*x == 0.0 || *x == 4.0 || *y == 0.0 || *y == 2.0);
+        }
+    }
+
+    #[test]
+    fn get_windows() {
+        // test continuous windows
+        let windows = super::get_windows(-1.5, 1.5, 0.5, 1.5, 1.0, 1.0);
+        assert_eq!(windows.len(), 8);
+        assert_eq!(windows[0], (-2.0, -1.0, 0.0, 1.0));
+        assert_eq!(windows[7], (1.0, 2.0, 1.0, 2.0));
+
+        // test antimeridian crossing windows
+        let windows = super::get_windows(178.5, -178.5,
+            0.5, 1.5, 1.0, 1.0);
+        assert_eq!(windows.len(), 8);
+        for (min_x, max_x, _, _) in windows.iter() {
+            assert!(*min_x >= -180.0 && *max_x <= 180.0);
+            assert!(*min_x >= 178.0 || *max_x <= -178.0);
+        }
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn get_ring_bounds() {
+        // test continuous ring
+        let xs = vec![10.0, 20.0, 20.0, 10.0];
+        let ys = vec![40.0, 40.0, 50.0, 50.0];
+        let bounds = super::get_ring_bounds(&xs, &ys, true);
+        assert_eq!(bounds, (10.0, 20.0, 40.0, 50.0));
+
+        // test antimeridian crossing ring
+        let xs = vec![170.0, -170.0, -170.0, 170.0];
+        let bounds = super::get_ring_bounds(&xs, &ys, true);
+        assert_eq!(bounds, (170.0, -170.0, 40.0, 50.0));
+
+        let bounds = super::get_ring_bounds(&xs, &ys, false);
+        assert_eq!(bounds, (-170.0, 170.0, 40.0, 50.0));
+
+        // test ring enclosing the north pole
+        let xs = vec![-135.0, -45.0, 45.0, 135.0];
+        let ys = vec![80.0, 80.0, 80.0, 80.0];
+        let bounds = super::get_ring_bounds(&xs, &ys, true);
+        assert_eq!(bounds, (-180.0, 180.0, 80.0, 90.0));
+    }
+
+    #[test]
+    fn invert_transform() {
+        // test north-up, south-up, and rotated transforms
+        let transforms = [
+            [-88.4, 0.01, 0.0, 44.266667, 0.0, -0.01],
+            [-88.4, 0.01, 0.0, 40.559167, 0.0, 0.01],
+            [500000.0, 8.660254, 5.0, 4500000.0, 5.0, -8.660254],
+        ];
+
+        for transform in transforms.iter() {
+            let geo_transform = GeoTransform::from(*transform);
+            let inverse: [f64; 6] = geo_transform.invert()
+                .expect("invert transform").into();
+
+            // validate pixel coordinate round trip
+            let (x, y) = (12.0, 34.0);
+            let cx = transform[0] + (x * transform[1])
+                + (y * transform[2]);
+            let cy = transform[3] + (x * transform[4])
+                + (y * transform[5]);
+
+            let px = inverse[0] + (cx * inverse[1]) + (cy * inverse[2]);
+            let py = inverse[3] + (cx * inverse[4]) + (cy * inverse[5]);
+            assert!((px - x).abs() < 0.000001);
+            assert!((py - y).abs() < 0.000001);
+
+            let (pxs, pys) = super::invert_coords(&[cx], &[cy],
+                &geo_transform).expect("invert coords");
+            assert_eq!((pxs[0], pys[0]), (px, py));
+        }
+
+        // test singular transform
+        let result = GeoTransform::new(0.0, 1.0, 1.0, 0.0, 1.0, 1.0)
+            .invert();
+        assert!(result.is_err());
+
+        // test small pixel geographic transform
+        let transform = GeoTransform::new(-88.4, 1e-8, 0.0,
+            44.266667, 0.0, -1e-8);
+        let inverse = transform.invert().expect("invert transform");
+
+        let (cx, cy) = transform.pixel_to_world(12.0, 34.0);
+        let (px, py) = inverse.pixel_to_world(cx, cy);
+        assert!((px - 12.0).abs() < 0.001);
+        assert!((py - 34.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn geo_transform() {
+        let transform = GeoTransform::new(500000.0, 30.0, 0.0,
+            4500000.0, 0.0, -30.0);
+        assert!(!transform.is_rotated());
+        assert_eq!(transform.origin(), (500000.0, 4500000.0));
+        assert_eq!((transform.pixel_width(), transform.pixel_height()),
+            (30.0, -30.0));
+
+        // validate pixel and world coordinate conversions
+        assert_eq!(transform.pixel_to_world(10.0, 20.0),
+            (500300.0, 4499400.0));
+        let (x, y) = transform.world_to_pixel(500300.0, 4499400.0)
+            .expect("world to pixel");
+        assert!((x - 10.0).abs() < 0.000001);
+        assert!((y - 20.0).abs() < 0.000001);
+
+        // offset and scale maintain the pixel grid
+        let offset = transform.offset(10.0, 20.0);
+        assert_eq!(offset.origin(), (500300.0, 4499400.0));
+        assert_eq!(offset.pixel_to_world(5.0, 5.0),
+            transform.pixel_to_world(15.0, 25.0));
+
+        let scale = transform.scale(2.0, 4.0);
+        assert_eq!(scale.origin(), transform.origin());
+        assert_eq!((scale.pixel_width(), scale.pixel_height()),
+            (60.0, -120.0));
+
+        // validate array conversions
+        let array: [f64; 6] = transform.into();
+        assert_eq!(array, [500000.0, 30.0, 0.0, 4500000.0, 0.0, -30.0]);
+        assert_eq!(GeoTransform::from(array), transform);
+
+        let rotated = GeoTransform::from(
+            [500000.0, 8.660254, 5.0, 4500000.0, 5.0, -8.660254]);
+        assert!(rotated.is_rotated());
+        let (x, y) = rotated.world_to_pixel(500123.0, 4499876.0)
+            .expect("world to pixel");
+        let (cx, cy) = rotated.pixel_to_world(x, y);
+        assert!((cx - 500123.0).abs() < 0.000001);
+        assert!((cy - 4499876.0).abs() < 0.000001);
+    }
+
+    #[cfg(feature = "gdal-backend")]
     #[test]
     fn transform_coord() {
         // initialize CoordTransform
@@ -215,11 +919,59 @@ mod tests {
         assert!((coordinates.1 - FORT_COLLINS_MERCATOR.1).abs() < 0.01);
     }
 
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn coord_to_pixel() {
+        let path = std::path::Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = gdal::Dataset::open(path).expect("open dataset");
+
+        let (transform, _, src_spatial_ref, dst_spatial_ref) =
+            super::get_transform_refs(&dataset, 4326)
+                .expect("transform refs");
+        let coord_transform = CoordTransform::new(&src_spatial_ref,
+            &dst_spatial_ref).expect("initialize CoordTransform");
+        let reverse_transform = CoordTransform::new(&dst_spatial_ref,
+            &src_spatial_ref).expect("initialize CoordTransform");
+
+        // validate pixel coordinate round trip through lat long
+        let (x, y, _) = super::transform_pixel(120, 340, 0,
+            &transform, &coord_transform).expect("transform pixel");
+        let (px, py) = super::coord_to_pixel(x, y,
+            &transform, &reverse_transform).expect("coord to pixel");
+        assert!((px - 120.0).abs() < 0.0001);
+        assert!((py - 340.0).abs() < 0.0001);
+
+        // points failing to transform keep their index
+        let pixels = super::coords_to_pixels(&[x, 0.0, x], &[y, 95.0, y],
+            &transform, &reverse_transform).expect("coords to pixels");
+        assert_eq!(pixels, vec![Some((px, py)), None, Some((px, py))]);
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn get_bounds() {
+        let path = std::path::Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = gdal::Dataset::open(path).expect("open dataset");
+
+        // default bounds are ordered
+        let (min_x, max_x, min_y, max_y) = super::get_bounds(&dataset, 4326)
+            .expect("get bounds");
+        assert!(min_x < max_x && min_y < max_y);
+
+        // wrapping only applies to rings crossing the antimeridian
+        let wrapped_bounds = super::get_bounds_densified(&dataset, 4326,
+            super::DENSIFY_POINTS, true).expect("get bounds");
+        assert_eq!(wrapped_bounds, (min_x, max_x, min_y, max_y));
+
+        let spatial_ref = SpatialRef::from_epsg(4326)
+            .expect("initialize SpatialRef");
+        assert!(super::is_geographic(&spatial_ref).expect("geographic"));
+        let spatial_ref = SpatialRef::from_epsg(3857)
+            .expect("initialize SpatialRef");
+        assert!(!super::is_geographic(&spatial_ref).expect("geographic"));
+    }
+
     // TODO - transform pixel
 
     // TODO - transform pixels
-
-    // TODO - test get_bounds
- 
-    // TODO - test get_windows
 }
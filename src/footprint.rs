@@ -0,0 +1,210 @@
+#[cfg(feature = "gdal-backend")]
+use gdal::Dataset;
+#[cfg(feature = "gdal-backend")]
+use gdal::spatial_ref::CoordTransform;
+
+#[cfg(feature = "gdal-backend")]
+use crate::backend::Backend;
+use crate::geometry::Geometry;
+
+#[cfg(feature = "gdal-backend")]
+use std::error::Error;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Footprint {
+    geometry: Geometry,
+}
+
+impl Footprint {
+    pub fn new(coordinates: Vec<(f64, f64)>) -> Footprint {
+        Footprint { geometry: Geometry::new(vec![coordinates]) }
+    }
+
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        self.geometry.bounds()
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        self.geometry.contains(x, y)
+    }
+
+    pub fn coordinates(&self) -> &[(f64, f64)] {
+        &self.geometry.rings()[0]
+    }
+
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    pub fn intersects(&self, min_x: f64, max_x: f64,
+            min_y: f64, max_y: f64) -> bool {
+        let coordinates = self.coordinates();
+        if coordinates.is_empty() {
+            return false;
+        }
+
+        // check if any footprint vertex falls within window
+        if coordinates.iter().any(|(x, y)| *x >= min_x
+                && *x <= max_x && *y >= min_y && *y <= max_y) {
+            return true;
+        }
+
+        // check if any window corner falls within footprint
+        let corners = [(min_x, min_y), (max_x, min_y),
+            (max_x, max_y), (min_x, max_y)];
+        if corners.iter().any(|(x, y)| self.contains(*x, *y)) {
+            return true;
+        }
+
+        // check if any footprint edge crosses a window edge
+        let count = coordinates.len();
+        for i in 0..count {
+            let start = coordinates[i];
+            let end = coordinates[(i + 1) % count];
+
+            for j in 0..corners.len() {
+                if segments_intersect(start, end, corners[j],
+                        corners[(j + 1) % corners.len()]) {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    pub fn to_geojson(&self) -> String {
+        let coordinates: Vec<String> = self.ring().iter()
+            .map(|(x, y)| format!("[{},{}]", x, y)).collect();
+
+        format!("{{\"type\":\"Polygon\",\"coordinates\":[[{}]]}}",
+            coordinates.join(","))
+    }
+
+    pub fn to_wkt(&self) -> String {
+        let coordinates: Vec<String> = self.ring().iter()
+            .map(|(x, y)| format!("{} {}", x, y)).collect();
+
+        format!("POLYGON (({}))", coordinates.join(", "))
+    }
+
+    fn ring(&self) -> Vec<(f64, f64)> {
+        // close ring by repeating first coordinate
+        let mut ring = self.coordinates().to_vec();
+        if let Some(coordinate) = ring.first().cloned() {
+            ring.push(coordinate);
+        }
+
+        ring
+    }
+}
+
+// the footprint is traced from the leftmost and rightmost valid pixel
+//   of each row, so it is a single ring that bridges concave gaps and
+//   separate valid areas within a row rather than an exact contour
+#[cfg(feature = "gdal-backend")]
+pub fn get_footprint(dataset: &Dataset, epsg_code: u32)
+        -> Result<Option<Footprint>, Box<dyn Error>> {
+    // identify valid pixel extents for each row
+    let (width, height) = dataset.raster_size();
+    let invalid_pixels = dataset.get_invalid_pixels()?;
+
+    let mut left_pixels = Vec::new();
+    let mut right_pixels = Vec::new();
+    for y in 0..height {
+        let row = &invalid_pixels[y * width..(y + 1) * width];
+        let min_x = match row.iter().position(|x| !*x) {
+            Some(min_x) => min_x,
+            None => continue,
+        };
+        let max_x = row.iter().rposition(|x| !*x).unwrap() + 1;
+
+        // add pixel corners along both sides of the row
+        push_vertex(&mut left_pixels, (min_x as f64, y as f64));
+        push_vertex(&mut left_pixels, (min_x as f64, (y + 1) as f64));
+        push_vertex(&mut right_pixels, (max_x as f64, y as f64));
+        push_vertex(&mut right_pixels, (max_x as f64, (y + 1) as f64));
+    }
+
+    if left_pixels.is_empty() {
+        return Ok(None);
+    }
+
+    // trace footprint down the left side and up the right side
+    let pixels: Vec<(f64, f64)> = left_pixels.into_iter()
+        .chain(right_pixels.into_iter().rev()).collect();
+
+    // convert pixels to coordinates
+    let (transform, _, src_spatial_ref, dst_spatial_ref) =
+        crate::coordinate::get_transform_refs(dataset, epsg_code)?;
+    let coord_transform = CoordTransform::new(
+        &src_spatial_ref, &dst_spatial_ref)?;
+
+    let (xs, ys): (Vec<f64>, Vec<f64>) = pixels.iter()
+        .map(|(x, y)| transform.pixel_to_world(*x, *y)).unzip();
+
+    let (xs, ys) = crate::coordinate::transform_valid_coords(
+        &xs, &ys, &coord_transform)?;
+
+    Ok(Some(Footprint::new(xs.into_iter().zip(ys).collect())))
+}
+
+#[cfg(feature = "gdal-backend")]
+fn push_vertex(vertices: &mut Vec<(f64, f64)>, vertex: (f64, f64)) {
+    // replace previous vertex if it is collinear along a pixel column
+    let length = vertices.len();
+    if length >= 2 && vertices[length - 1].0 == vertex.0
+            && vertices[length - 2].0 == vertex.0 {
+        vertices[length - 1] = vertex;
+    } else {
+        vertices.push(vertex);
+    }
+}
+
+fn segments_intersect(a: (f64, f64), b: (f64, f64),
+        c: (f64, f64), d: (f64, f64)) -> bool {
+    let orientation = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        ((q.0 - p.0) * (r.1 - p.1)) - ((q.1 - p.1) * (r.0 - p.0))
+    };
+
+    let (o1, o2) = (orientation(a, b, c), orientation(a, b, d));
+    let (o3, o4) = (orientation(c, d, a), orientation(c, d, b));
+
+    (o1 * o2) < 0.0 && (o3 * o4) < 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Footprint;
+
+    #[test]
+    fn footprint_intersects() {
+        // diagonal swath footprint
+        let footprint = Footprint::new(vec![(0.0, 0.0), (1.0, 0.0),
+            (4.0, 3.0), (4.0, 4.0), (3.0, 4.0), (0.0, 1.0)]);
+
+        assert!(footprint.contains(2.0, 2.0));
+        assert!(!footprint.contains(3.5, 0.5));
+        assert_eq!(footprint.bounds(), (0.0, 4.0, 0.0, 4.0));
+
+        // window along the swath
+        assert!(footprint.intersects(1.5, 2.5, 1.5, 2.5));
+
+        // window straddling the swath edge
+        assert!(footprint.intersects(1.9, 2.1, 0.5, 1.5));
+
+        // windows outside the swath
+        assert!(!footprint.intersects(3.0, 4.0, 0.0, 1.0));
+        assert!(!footprint.intersects(0.0, 1.0, 3.0, 4.0));
+    }
+
+    #[test]
+    fn footprint_export() {
+        let footprint = Footprint::new(vec![(0.0, 0.0),
+            (1.0, 0.0), (1.0, 1.0)]);
+
+        assert_eq!(footprint.to_wkt(), "POLYGON ((0 0, 1 0, 1 1, 0 0))");
+        assert_eq!(footprint.to_geojson(), "{\"type\":\"Polygon\",\
+            \"coordinates\":[[[0,0],[1,0],[1,1],[0,0]]]}");
+    }
+}
@@ -0,0 +1,355 @@
+#[cfg(feature = "gdal-backend")]
+use gdal::spatial_ref::CoordTransform;
+#[cfg(feature = "gdal-backend")]
+use gdal_sys::{OGRErr, OGRGeometryH, OGRwkbGeometryType};
+
+use crate::coordinate::GeoTransform;
+#[cfg(feature = "gdal-backend")]
+use crate::coordinate::DENSIFY_POINTS;
+
+use std::error::Error;
+#[cfg(feature = "gdal-backend")]
+use std::ffi::CString;
+#[cfg(feature = "gdal-backend")]
+use std::os::raw::c_char;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Geometry {
+    rings: Vec<Vec<(f64, f64)>>,
+}
+
+impl Geometry {
+    pub fn new(rings: Vec<Vec<(f64, f64)>>) -> Geometry {
+        Geometry { rings }
+    }
+
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        let mut bounds = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        for (x, y) in self.rings.iter().flatten() {
+            bounds.0 = bounds.0.min(*x);
+            bounds.1 = bounds.1.max(*x);
+            bounds.2 = bounds.2.min(*y);
+            bounds.3 = bounds.3.max(*y);
+        }
+
+        bounds
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        // even-odd rule over all rings handles holes and multipolygons
+        self.get_crossings(y).iter().filter(|cx| **cx > x).count() % 2 == 1
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub(crate) unsafe fn from_c_geometry(c_geometry: OGRGeometryH)
+            -> Result<Geometry, Box<dyn Error>> {
+        let mut rings = Vec::new();
+        match gdal_sys::OGR_GT_Flatten(
+                gdal_sys::OGR_G_GetGeometryType(c_geometry)) {
+            OGRwkbGeometryType::wkbPolygon =>
+                push_rings(c_geometry, &mut rings),
+            OGRwkbGeometryType::wkbMultiPolygon => {
+                for i in 0..gdal_sys::OGR_G_GetGeometryCount(c_geometry) {
+                    push_rings(gdal_sys::OGR_G_GetGeometryRef(c_geometry, i),
+                        &mut rings);
+                }
+            },
+            _ => return Err("geometry must be a polygon or multipolygon"
+                .into()),
+        }
+
+        Ok(Geometry::new(rings))
+    }
+
+    pub fn get_mask(&self, offset: (isize, isize), size: (usize, usize))
+            -> Vec<bool> {
+        let (x_offset, y_offset) = offset;
+        let (width, height) = size;
+
+        // fill pixel centers between scanline crossings
+        let mut mask = vec![false; width * height];
+        for row in 0..height {
+            let y = (y_offset + row as isize) as f64 + 0.5;
+            let crossings = self.get_crossings(y);
+
+            for pair in crossings.chunks(2).filter(|x| x.len() == 2) {
+                let min_col = (((pair[0] - 0.5).ceil() as isize)
+                    - x_offset).max(0);
+                let max_col = (((pair[1] - 0.5).ceil() as isize)
+                    - x_offset).min(width as isize);
+
+                for col in min_col..max_col {
+                    mask[(row * width) + col as usize] = true;
+                }
+            }
+        }
+
+        mask
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub fn parse(geometry: &str) -> Result<Geometry, Box<dyn Error>> {
+        // parse geojson objects or wkt strings
+        let geometry = geometry.trim();
+        let c_str = CString::new(geometry)?;
+        let c_geometry = unsafe {
+            match geometry.starts_with('{') {
+                true => gdal_sys::OGR_G_CreateGeometryFromJson(
+                    c_str.as_ptr()),
+                false => {
+                    let mut c_wkt = c_str.as_ptr() as *mut c_char;
+                    let mut c_geometry = std::ptr::null_mut();
+                    if gdal_sys::OGR_G_CreateFromWkt(&mut c_wkt,
+                            std::ptr::null_mut(), &mut c_geometry)
+                            != OGRErr::OGRERR_NONE {
+                        return Err("failed to parse wkt geometry".into());
+                    }
+
+                    c_geometry
+                },
+            }
+        };
+
+        if c_geometry.is_null() {
+            return Err("failed to parse geometry".into());
+        }
+
+        let result = unsafe { Geometry::from_c_geometry(c_geometry) };
+        unsafe { gdal_sys::OGR_G_DestroyGeometry(c_geometry) };
+        result
+    }
+
+    pub fn rings(&self) -> &[Vec<(f64, f64)>] {
+        &self.rings
+    }
+
+    pub fn to_pixels(&self, transform: &GeoTransform)
+            -> Result<Geometry, Box<dyn Error>> {
+        let mut rings = Vec::new();
+        for ring in self.rings.iter() {
+            let (xs, ys): (Vec<f64>, Vec<f64>) = ring.iter().cloned().unzip();
+            let (pixel_xs, pixel_ys) =
+                crate::coordinate::invert_coords(&xs, &ys, transform)?;
+            rings.push(pixel_xs.into_iter().zip(pixel_ys).collect());
+        }
+
+        Ok(Geometry::new(rings))
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub fn transform(&self, coord_transform: &CoordTransform)
+            -> Result<Geometry, Box<dyn Error>> {
+        let mut rings = Vec::new();
+        for ring in self.rings.iter() {
+            // densify edges so they follow the destination curvature
+            let ring = densify_ring(ring, DENSIFY_POINTS);
+            let (mut xs, mut ys): (Vec<f64>, Vec<f64>) =
+                ring.into_iter().unzip();
+
+            let mut zs = vec![0.0; xs.len()];
+            if coord_transform.transform_coords(&mut xs,
+                    &mut ys, &mut zs).is_err() {
+                return Err("failed to transform geometry vertex".into());
+            }
+
+            rings.push(xs.into_iter().zip(ys).collect());
+        }
+
+        Ok(Geometry::new(rings))
+    }
+
+    fn get_crossings(&self, y: f64) -> Vec<f64> {
+        // compute sorted x coordinates where edges cross the line
+        let mut crossings = Vec::new();
+        for ring in self.rings.iter() {
+            let count = ring.len();
+            for i in 0..count {
+                let (start_x, start_y) = ring[i];
+                let (end_x, end_y) = ring[(i + 1) % count];
+
+                if (start_y > y) != (end_y > y) {
+                    crossings.push(start_x + ((y - start_y)
+                        * (end_x - start_x) / (end_y - start_y)));
+                }
+            }
+        }
+
+        crossings.sort_by(|a, b| a.partial_cmp(b)
+            .unwrap_or(std::cmp::Ordering::Equal));
+        crossings
+    }
+}
+
+#[cfg(feature = "gdal-backend")]
+fn densify_ring(ring: &[(f64, f64)], points: usize) -> Vec<(f64, f64)> {
+    let count = ring.len();
+    if count < 2 {
+        return ring.to_vec();
+    }
+
+    // closed rings repeat the first vertex rather than wrapping
+    let closed = ring.first() == ring.last();
+    let edge_count = match closed {
+        true => count - 1,
+        false => count,
+    };
+
+    let points = points.max(1);
+    let mut densified = Vec::with_capacity((edge_count * points) + 1);
+    for i in 0..edge_count {
+        let (start_x, start_y) = ring[i];
+        let (end_x, end_y) = ring[(i + 1) % count];
+
+        for j in 0..points {
+            let ratio = j as f64 / points as f64;
+            densified.push((start_x + ((end_x - start_x) * ratio),
+                start_y + ((end_y - start_y) * ratio)));
+        }
+    }
+
+    if closed {
+        densified.push(ring[count - 1]);
+    }
+
+    densified
+}
+
+#[cfg(feature = "gdal-backend")]
+unsafe fn push_rings(c_polygon: OGRGeometryH,
+        rings: &mut Vec<Vec<(f64, f64)>>) {
+    // exterior ring followed by interior rings
+    for i in 0..gdal_sys::OGR_G_GetGeometryCount(c_polygon) {
+        let c_ring = gdal_sys::OGR_G_GetGeometryRef(c_polygon, i);
+
+        let mut ring = Vec::new();
+        for j in 0..gdal_sys::OGR_G_GetPointCount(c_ring) {
+            let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+            gdal_sys::OGR_G_GetPoint(c_ring, j, &mut x, &mut y, &mut z);
+            ring.push((x, y));
+        }
+
+        rings.push(ring);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Geometry;
+    use crate::coordinate::GeoTransform;
+
+    fn square_with_hole() -> Geometry {
+        Geometry::new(vec![
+            vec![(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (0.0, 0.0)],
+            vec![(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0), (1.0, 1.0)],
+        ])
+    }
+
+    #[test]
+    fn geometry_contains() {
+        let geometry = square_with_hole();
+        assert_eq!(geometry.bounds(), (0.0, 4.0, 0.0, 4.0));
+
+        assert!(geometry.contains(0.5, 0.5));
+        assert!(geometry.contains(3.5, 1.5));
+        assert!(!geometry.contains(1.5, 1.5));
+        assert!(!geometry.contains(4.5, 1.5));
+    }
+
+    #[test]
+    fn geometry_mask() {
+        let geometry = square_with_hole();
+
+        // pixel centers within the hole are excluded
+        let mask = geometry.get_mask((0, 0), (5, 3));
+        assert_eq!(mask, vec![
+            true, true, true, true, false,
+            true, false, true, true, false,
+            true, true, true, true, false,
+        ]);
+
+        // offset window shares the same pixel grid
+        let mask = geometry.get_mask((1, 1), (2, 2));
+        assert_eq!(mask, vec![false, true, true, true]);
+
+        // geometry is converted to pixel space by the inverse transform
+        let geometry = Geometry::new(vec![vec![(10.0, 20.0),
+            (14.0, 20.0), (14.0, 16.0), (10.0, 16.0)]]);
+        let pixel_geometry = geometry.to_pixels(&GeoTransform::new(
+            10.0, 2.0, 0.0, 20.0, 0.0, -2.0)).expect("to pixels");
+        assert_eq!(pixel_geometry.bounds(), (0.0, 2.0, 0.0, 2.0));
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn geometry_densify() {
+        // closed rings keep their closing vertex
+        let ring = super::densify_ring(&[(0.0, 0.0), (4.0, 0.0),
+            (4.0, 4.0), (0.0, 0.0)], 2);
+        assert_eq!(ring, vec![(0.0, 0.0), (2.0, 0.0), (4.0, 0.0),
+            (4.0, 2.0), (4.0, 4.0), (2.0, 2.0), (0.0, 0.0)]);
+
+        // open rings densify the wrapping edge
+        let ring = super::densify_ring(&[(0.0, 0.0), (4.0, 0.0),
+            (4.0, 4.0)], 2);
+        assert_eq!(ring, vec![(0.0, 0.0), (2.0, 0.0), (4.0, 0.0),
+            (4.0, 2.0), (4.0, 4.0), (2.0, 2.0)]);
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn geometry_transform() {
+        use gdal::spatial_ref::{CoordTransform, SpatialRef};
+
+        let src_spatial_ref = SpatialRef::from_epsg(4326)
+            .expect("src spatial ref");
+        let dst_spatial_ref = SpatialRef::from_epsg(3857)
+            .expect("dst spatial ref");
+
+        #[cfg(major_ge_3)]
+        {
+            use gdal_sys::OSRAxisMappingStrategy;
+            src_spatial_ref.set_axis_mapping_strategy(
+                OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+            dst_spatial_ref.set_axis_mapping_strategy(
+                OSRAxisMappingStrategy::OAMS_TRADITIONAL_GIS_ORDER);
+        }
+
+        let coord_transform = CoordTransform::new(&src_spatial_ref,
+            &dst_spatial_ref).expect("coord transform");
+
+        // edges are densified before reprojection
+        let geometry = square_with_hole();
+        let transformed = geometry.transform(&coord_transform)
+            .expect("transform geometry");
+        for (ring, transformed_ring) in geometry.rings().iter()
+                .zip(transformed.rings().iter()) {
+            assert_eq!(transformed_ring.len(),
+                ((ring.len() - 1) * crate::coordinate::DENSIFY_POINTS) + 1);
+        }
+
+        let (min_x, max_x, _, _) = transformed.bounds();
+        assert!(min_x.abs() < 1e-6);
+        assert!((max_x - 445277.96).abs() < 0.01);
+
+        // vertices failing to transform are errors rather than dropped
+        let geometry = Geometry::new(vec![vec![(0.0, 0.0),
+            (4.0, 0.0), (4.0, 95.0), (0.0, 0.0)]]);
+        assert!(geometry.transform(&coord_transform).is_err());
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn geometry_parse() {
+        let wkt = Geometry::parse("POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0), \
+            (1 1, 2 1, 2 2, 1 2, 1 1))").expect("parse wkt");
+        assert_eq!(wkt, square_with_hole());
+
+        let geojson = Geometry::parse("{\"type\": \"MultiPolygon\", \
+            \"coordinates\": [[[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]], \
+            [[1, 1], [2, 1], [2, 2], [1, 2], [1, 1]]]]}")
+            .expect("parse geojson");
+        assert_eq!(geojson, square_with_hole());
+
+        assert!(Geometry::parse("POINT (0 0)").is_err());
+    }
+}
@@ -1,12 +1,39 @@
+#[cfg(feature = "gdal-backend")]
 use gdal::{Dataset, Driver};
+#[cfg(feature = "gdal-backend")]
 use gdal::raster::{Buffer, GdalType};
-use gdal_sys::GDALDataType;
+#[cfg(feature = "gdal-backend")]
+use gdal_sys::{CPLErr, GDALDataType, GDALRasterIOExtraArg, GDALRWFlag};
 
-use std::error::Error;
+use crate::backend::Backend;
+use crate::raster::AnyRaster;
 
+use std::error::Error;
+#[cfg(feature = "gdal-backend")]
+use std::ffi::{CStr, CString};
+#[cfg(feature = "gdal-backend")]
+use std::os::raw::{c_int, c_void};
+
+#[cfg(feature = "ndarray")]
+pub mod array;
+pub mod backend;
+#[cfg(feature = "gdal-backend")]
+pub mod clip;
 pub mod coordinate;
+pub mod footprint;
+pub mod geometry;
+pub mod raster;
+#[cfg(feature = "gdal-backend")]
+pub mod render;
+pub mod sample;
 pub mod serialize;
+#[cfg(feature = "gdal-backend")]
+pub mod store;
+#[cfg(feature = "gdal-backend")]
 pub mod transform;
+#[cfg(feature = "gdal-backend")]
+pub mod warp;
+pub mod zonal;
 
 pub trait FromPrimitive {
     fn from_f64(value: f64) -> Self;
@@ -36,134 +63,37 @@ impl FromPrimitive for f32 {
     }
 }
 
-pub fn get_coverage(dataset: &Dataset) -> Result<f64, Box<dyn Error>> {
-    let (width, height) = dataset.raster_size();
-    let mut invalid_pixels = vec![true; width * height];
-    
-    // iterate over rasterbands
-    for i in 0..dataset.raster_count() {
-        let rasterband = dataset.rasterband(i+1)?;
-        let no_data_value = rasterband.no_data_value().unwrap_or(0.0);
-
-        match rasterband.band_type() {
-            GDALDataType::GDT_Byte => _get_coverage::<u8>(dataset,
-                i+1, &mut invalid_pixels, no_data_value)?,
-            GDALDataType::GDT_Int16 => _get_coverage::<i16>(dataset,
-                i+1, &mut invalid_pixels, no_data_value)?,
-            GDALDataType::GDT_UInt16 => _get_coverage::<u16>(dataset,
-                i+1, &mut invalid_pixels, no_data_value)?,
-            GDALDataType::GDT_Float32 => _get_coverage::<f32>(dataset,
-                i+1, &mut invalid_pixels, no_data_value)?,
-            _ => unimplemented!(),
-        }
-    }
+pub fn get_coverage<B: Backend>(dataset: &B)
+        -> Result<f64, Box<dyn Error>> {
+    let invalid_pixels = dataset.get_invalid_pixels()?;
 
     // compute percentage of valid pixels
-    let pixel_count = (width * height) as f64;
+    let pixel_count = invalid_pixels.len() as f64;
     let invalid_count = invalid_pixels.iter()
         .filter(|x| **x).count() as f64;
 
     Ok((pixel_count - invalid_count) / pixel_count)
 }
 
-fn _get_coverage<T: Copy + FromPrimitive + GdalType + PartialEq>(
-        dataset: &Dataset, index: isize, invalid_pixels: &mut Vec<bool>,
-        no_data_value: f64) -> Result<(), Box<dyn Error>> {
-    let no_data_value = T::from_f64(no_data_value);
-
-    // read rasterband data into buffer
-    let buffer = dataset.rasterband(index)?.read_band_as::<T>()?;
-
-    // iterate over pixels
-    for (i, pixel) in buffer.data.iter().enumerate() {
-        if *pixel != no_data_value {
-            invalid_pixels[i] = false;
-        }
-    }
-
-    Ok(())
-}
-
-pub fn fill(datasets: &[Dataset]) -> Result<Dataset, Box<dyn Error>> {
-    let rasterband = datasets[0].rasterband(1)?;
-    let no_data_value = rasterband.no_data_value();
-
-    match rasterband.band_type() {
-        GDALDataType::GDT_Byte => _fill::<u8>(datasets, no_data_value),
-        GDALDataType::GDT_Int16 => 
-            _fill::<i16>(datasets, no_data_value),
-        GDALDataType::GDT_UInt16 =>
-            _fill::<u16>(datasets, no_data_value),
-        _ => unimplemented!(),
-    }
+pub(crate) fn percentile(sorted_values: &[f64], percentile: f64) -> f64 {
+    // nearest rank over sorted values
+    let position = (percentile.clamp(0.0, 100.0) / 100.0)
+        * (sorted_values.len() - 1) as f64;
+    sorted_values[position.round() as usize]
 }
 
-fn _fill<T: Copy + FromPrimitive + GdalType + PartialEq>(
-        datasets: &[Dataset], no_data_option: Option<f64>)
-        -> Result<Dataset, Box<dyn Error>> {
-    let no_data_value = T::from_f64(no_data_option.unwrap_or(0.0));
-    let dataset = &datasets[0];
-
-    // read first dataset rasters
-    let mut rasters = Vec::new();
-    for i in 0..dataset.raster_count() {
-        let raster = dataset.rasterband(i+1)?.read_band_as::<T>()?;
-        rasters.push(raster);
-    }
-
-    // fill with remaining datasets
-    for fill_dataset in datasets.iter().skip(1) {
-        // read fill dataset rasterbands
-        let mut fill_rasters = Vec::new();
-        for j in 0..fill_dataset.raster_count() {
-            let fill_raster = fill_dataset.rasterband(j+1)?
-                .read_band_as::<T>()?;
-            fill_rasters.push(fill_raster);
-        }
+pub fn fill<B: Backend>(datasets: &[B]) -> Result<B, Box<dyn Error>> {
+    // read datasets into rasters
+    let rasters = datasets.iter()
+        .map(|x| x.to_raster().map(|x| x.into_owned()))
+        .collect::<Result<Vec<AnyRaster>, _>>()?;
 
-        // iterate over pixels
-        let size = rasters[0].data.len();
-        for j in 0..size {
-            if fill_rasters[0].data.len() <= j {
-                break;
-            }
-
-            // check if rasterband pixel is valid
-            let mut valid = false;
-            for raster in rasters.iter() {
-                valid = valid || raster.data[j] != no_data_value;
-            }
-
-            // copy pixels from fill_raster bands
-            if !valid {
-                for k in 0..rasters.len() {
-                    rasters[k].data[j] = fill_rasters[k].data[j];
-                }
-            }
-        }
-    }
-
-    // open memory dataset
-    let (width, height) = dataset.raster_size();
-    let driver = Driver::get("Mem")?;
-    let mem_dataset = crate::init_dataset(&driver, "unreachable",
-        T::gdal_type(), width as isize, height as isize,
-        rasters.len() as isize, no_data_option)?;
-
-    mem_dataset.set_geo_transform(
-        &dataset.geo_transform()?)?;
-    mem_dataset.set_projection(
-        &dataset.projection())?;
-
-    // set rasterbands
-    for (i, raster) in rasters.iter().enumerate() {
-        mem_dataset.rasterband((i+1) as isize)?.write::<T>((0, 0),
-            (width, height), &raster)?;
-    }
-
-    Ok(mem_dataset)
+    // fill first raster with remaining rasters
+    let raster = AnyRaster::fill(&rasters)?;
+    B::from_raster(raster)
 }
 
+#[cfg(feature = "gdal-backend")]
 pub fn init_dataset(driver: &Driver, filename: &str,
         gdal_type: GDALDataType::Type, width: isize, height: isize,
         rasterband_count: isize, no_data_value: Option<f64>)
@@ -177,10 +107,12 @@ pub fn init_dataset(driver: &Driver, filename: &str,
             filename, width, height, rasterband_count, no_data_value),
         GDALDataType::GDT_Float32 => _init_dataset::<f32>(driver,
             filename, width, height, rasterband_count, no_data_value),
-        _ => unimplemented!(),
+        band_type => Err(format!(
+            "unsupported band type '{}'", band_type).into()),
     }
 }
 
+#[cfg(feature = "gdal-backend")]
 pub fn _init_dataset<T: Copy + FromPrimitive + GdalType>(
         driver: &Driver, filename: &str, width: isize, height: isize,
         rasterband_count: isize, no_data_value: Option<f64>)
@@ -209,6 +141,43 @@ pub fn _init_dataset<T: Copy + FromPrimitive + GdalType>(
     Ok(dataset)
 }
 
+#[cfg(feature = "gdal-backend")]
+pub fn get_metadata_item(dataset: &Dataset, key: &str)
+        -> Result<Option<String>, Box<dyn Error>> {
+    let c_key = CString::new(key)?;
+    let rv = unsafe {
+        gdal_sys::GDALGetMetadataItem(dataset.c_dataset(), c_key.as_ptr(),
+            std::ptr::null())
+    };
+
+    match rv.is_null() {
+        true => Ok(None),
+        false => {
+            let c_str = unsafe { CStr::from_ptr(rv) };
+            Ok(Some(c_str.to_string_lossy().into_owned()))
+        },
+    }
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn set_metadata_item(dataset: &Dataset, key: &str, value: &str)
+        -> Result<(), Box<dyn Error>> {
+    let c_key = CString::new(key)?;
+    let c_value = CString::new(value)?;
+
+    let rv = unsafe {
+        gdal_sys::GDALSetMetadataItem(dataset.c_dataset(), c_key.as_ptr(),
+            c_value.as_ptr(), std::ptr::null())
+    };
+
+    if rv != CPLErr::CE_None {
+        return Err(format!("failed to set metadata item '{}'", key).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "gdal-backend")]
 pub fn copy_raster(src_dataset: &Dataset, src_index: isize,
         src_window: (isize, isize), src_window_size: (usize, usize),
         dst_dataset: &Dataset, dst_index: isize, 
@@ -227,10 +196,12 @@ pub fn copy_raster(src_dataset: &Dataset, src_index: isize,
         GDALDataType::GDT_Float32 => _copy_raster::<f32>(src_dataset, 
             src_index, src_window, src_window_size, dst_dataset, 
             dst_index, dst_window, dst_window_size),
-        _ => unimplemented!(),
+        band_type => Err(format!(
+            "unsupported band type '{}'", band_type).into()),
     }
 }
 
+#[cfg(feature = "gdal-backend")]
 fn _copy_raster<T: Copy + GdalType>(src_dataset: &Dataset,
         src_index: isize, src_window: (isize, isize), 
         src_window_size: (usize, usize), dst_dataset: &Dataset,
@@ -252,3 +223,82 @@ fn _copy_raster<T: Copy + GdalType>(src_dataset: &Dataset,
 
     Ok(())
 }
+
+#[cfg(feature = "gdal-backend")]
+pub fn resample_raster(src_dataset: &Dataset, src_index: isize,
+        src_window: (isize, isize), src_window_size: (usize, usize),
+        dst_dataset: &Dataset, dst_index: isize,
+        dst_window: (isize, isize), dst_window_size: (usize, usize),
+        resampling: transform::Resampling) -> Result<(), Box<dyn Error>> {
+    match src_dataset.rasterband(src_index)?.band_type() {
+        GDALDataType::GDT_Byte => _resample_raster::<u8>(src_dataset,
+            src_index, src_window, src_window_size, dst_dataset,
+            dst_index, dst_window, dst_window_size, resampling),
+        GDALDataType::GDT_Int16 => _resample_raster::<i16>(src_dataset,
+            src_index, src_window, src_window_size, dst_dataset,
+            dst_index, dst_window, dst_window_size, resampling),
+        GDALDataType::GDT_UInt16 => _resample_raster::<u16>(src_dataset,
+            src_index, src_window, src_window_size, dst_dataset,
+            dst_index, dst_window, dst_window_size, resampling),
+        GDALDataType::GDT_Float32 => _resample_raster::<f32>(src_dataset,
+            src_index, src_window, src_window_size, dst_dataset,
+            dst_index, dst_window, dst_window_size, resampling),
+        band_type => Err(format!(
+            "unsupported band type '{}'", band_type).into()),
+    }
+}
+
+#[cfg(feature = "gdal-backend")]
+fn _resample_raster<T: Copy + GdalType>(src_dataset: &Dataset,
+        src_index: isize, src_window: (isize, isize),
+        src_window_size: (usize, usize), dst_dataset: &Dataset,
+        dst_index: isize, dst_window: (isize, isize),
+        dst_window_size: (usize, usize),
+        resampling: transform::Resampling)
+        -> Result<(), Box<dyn Error>> {
+    // initialize extra arguments with resampling algorithm
+    let mut extra_arg = GDALRasterIOExtraArg {
+        nVersion: 1,
+        eResampleAlg: resampling.to_rio_resample_alg(),
+        pfnProgress: None,
+        pProgressData: std::ptr::null_mut(),
+        bFloatingPointWindowValidity: 0,
+        dfXOff: 0.0,
+        dfYOff: 0.0,
+        dfXSize: 0.0,
+        dfYSize: 0.0,
+    };
+
+    // read rasterband data into buffer using the resampling algorithm
+    let (buf_width, buf_height) = dst_window_size;
+    let mut data: Vec<T> = Vec::with_capacity(buf_width * buf_height);
+    unsafe {
+        let c_rasterband = gdal_sys::GDALGetRasterBand(
+            src_dataset.c_dataset(), src_index as c_int);
+        let rv = gdal_sys::GDALRasterIOEx(c_rasterband,
+            GDALRWFlag::GF_Read, src_window.0 as c_int,
+            src_window.1 as c_int, src_window_size.0 as c_int,
+            src_window_size.1 as c_int,
+            data.as_mut_ptr() as *mut c_void, buf_width as c_int,
+            buf_height as c_int, T::gdal_type(), 0, 0, &mut extra_arg);
+        if rv != CPLErr::CE_None {
+            return Err("failed to read resampled raster".into());
+        }
+
+        data.set_len(buf_width * buf_height);
+    }
+
+    let buffer = Buffer::new(dst_window_size, data);
+
+    // write to new rasterband
+    let src_rasterband = src_dataset.rasterband(src_index)?;
+    let dst_rasterband = dst_dataset.rasterband(dst_index)?;
+    dst_rasterband.write::<T>(dst_window, dst_window_size, &buffer)?;
+
+    // maintain rasterband metadata
+    if let Some(value) = src_rasterband.no_data_value() {
+        dst_rasterband.set_no_data_value(value)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,520 @@
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use gdal::{Dataset, Driver};
+use serde_json::{json, Value};
+use st_image::coordinate::Geocode;
+use st_image::render::{Colormap, ImageFormat, RenderOptions, Stretch};
+use st_image::sample::Interpolation;
+use st_image::transform::SplitOptions;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+type Window = (f64, f64, f64, f64);
+
+fn main() {
+    let matches = app().get_matches();
+
+    // execute subcommand
+    let (subcommand, sub_matches) = matches.subcommand();
+    let sub_matches = sub_matches.expect("subcommand matches");
+    let result = execute(subcommand, sub_matches);
+
+    // print result
+    let json = matches.is_present("json") || sub_matches.is_present("json");
+    match result {
+        Ok(value) if json => println!("{}", value),
+        Ok(value) => print_text(&value),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        },
+    }
+}
+
+fn app() -> App<'static, 'static> {
+    let geocode_arg = Arg::with_name("geocode")
+        .short("g").long("geocode").takes_value(true)
+        .possible_values(&["geohash", "quadtile"])
+        .default_value("geohash").help("geocode system");
+    let precision_arg = Arg::with_name("precision")
+        .short("p").long("precision").takes_value(true)
+        .required(true).help("geocode precision");
+
+    App::new("st-image")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("manage spatiotemporal images")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(Arg::with_name("json").short("j").long("json")
+            .global(true).help("print output as json"))
+        .subcommand(SubCommand::with_name("bounds")
+            .about("compute image bounds")
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(Arg::with_name("epsg_code")
+                .short("e").long("epsg-code").takes_value(true)
+                .default_value("4326").help("bounds epsg code")))
+        .subcommand(SubCommand::with_name("windows")
+            .about("compute geocode windows covering an image")
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(geocode_arg.clone())
+            .arg(precision_arg.clone()))
+        .subcommand(SubCommand::with_name("split")
+            .about("split an image along geocode windows")
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(Arg::with_name("DIRECTORY").required(true))
+            .arg(geocode_arg)
+            .arg(precision_arg)
+            .arg(Arg::with_name("min_coverage")
+                .short("m").long("min-coverage").takes_value(true)
+                .default_value("0").help("minimum tile coverage")))
+        .subcommand(SubCommand::with_name("merge")
+            .about("merge images into a single image")
+            .arg(Arg::with_name("OUTPUT").required(true))
+            .arg(Arg::with_name("FILES").required(true).multiple(true)))
+        .subcommand(SubCommand::with_name("fill")
+            .about("fill image no_data pixels from other images")
+            .arg(Arg::with_name("OUTPUT").required(true))
+            .arg(Arg::with_name("FILES").required(true).multiple(true)))
+        .subcommand(SubCommand::with_name("coverage")
+            .about("compute percentage of valid image pixels")
+            .arg(Arg::with_name("FILE").required(true)))
+        .subcommand(SubCommand::with_name("render")
+            .about("render an image to png or jpeg")
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(Arg::with_name("OUTPUT").required(true))
+            .arg(Arg::with_name("bands")
+                .short("b").long("bands").takes_value(true)
+                .default_value("1,2,3").help("rendered band combination"))
+            .arg(Arg::with_name("colormap")
+                .short("c").long("colormap").takes_value(true)
+                .possible_values(&["viridis"]).help("single band colormap"))
+            .arg(Arg::with_name("gamma")
+                .long("gamma").takes_value(true)
+                .default_value("1").help("gamma correction"))
+            .arg(Arg::with_name("percentile")
+                .long("percentile").help("stretch by percentiles"))
+            .arg(Arg::with_name("size")
+                .long("size").takes_value(true)
+                .help("rendered dimensions 'width,height'"))
+            .arg(Arg::with_name("stretch")
+                .short("s").long("stretch").takes_value(true)
+                .default_value("0,255").help("stretch 'min,max'")))
+        .subcommand(SubCommand::with_name("sample")
+            .about("sample image values at points")
+            .setting(AppSettings::AllowLeadingHyphen)
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(Arg::with_name("POINTS").required(true).multiple(true)
+                .help("sampled points 'x,y'"))
+            .arg(Arg::with_name("bilinear")
+                .long("bilinear").help("bilinear interpolation"))
+            .arg(Arg::with_name("epsg_code")
+                .short("e").long("epsg-code").takes_value(true)
+                .default_value("4326").help("points epsg code")))
+        .subcommand(SubCommand::with_name("encode")
+            .about("serialize an image")
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(Arg::with_name("OUTPUT").required(true)))
+        .subcommand(SubCommand::with_name("decode")
+            .about("deserialize an image")
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(Arg::with_name("OUTPUT").required(true)))
+        .subcommand(SubCommand::with_name("zonal")
+            .about("compute image statistics within zones")
+            .arg(Arg::with_name("FILE").required(true))
+            .arg(Arg::with_name("ZONES").required(true)
+                .help("geojson or ogr vector file"))
+            .arg(Arg::with_name("epsg_code")
+                .short("e").long("epsg-code").takes_value(true)
+                .default_value("4326").help("zones epsg code"))
+            .arg(Arg::with_name("id_field")
+                .short("i").long("id-field").takes_value(true)
+                .help("zone id attribute, defaults to feature id"))
+            .arg(Arg::with_name("layer")
+                .short("l").long("layer").takes_value(true)
+                .help("zone layer name, defaults to the first layer"))
+            .arg(Arg::with_name("percentiles")
+                .long("percentiles").takes_value(true)
+                .default_value("25,50,75").help("computed percentiles")))
+}
+
+fn execute(subcommand: &str, matches: &ArgMatches)
+        -> Result<Value, Box<dyn Error>> {
+    match subcommand {
+        "bounds" => bounds(matches),
+        "windows" => windows(matches),
+        "split" => split(matches),
+        "merge" => merge(matches),
+        "fill" => fill(matches),
+        "coverage" => coverage(matches),
+        "render" => render(matches),
+        "sample" => sample(matches),
+        "encode" => encode(matches),
+        "decode" => decode(matches),
+        "zonal" => zonal(matches),
+        _ => unreachable!(),
+    }
+}
+
+fn bounds(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = Path::new(matches.value_of("FILE").unwrap());
+    let dataset = Dataset::open(path)?;
+    let epsg_code = matches.value_of("epsg_code").unwrap().parse::<u32>()?;
+
+    let (min_x, max_x, min_y, max_y) =
+        st_image::coordinate::get_bounds(&dataset, epsg_code)?;
+
+    Ok(json!({"min_x": min_x, "max_x": max_x,
+        "min_y": min_y, "max_y": max_y}))
+}
+
+fn windows(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = Path::new(matches.value_of("FILE").unwrap());
+    let dataset = Dataset::open(path)?;
+    let (geocode, precision) = parse_geocode(matches)?;
+
+    let mut values = Vec::new();
+    for (code, window) in get_windows(&dataset, geocode, precision)? {
+        values.push(json!({"code": code, "window": window}));
+    }
+
+    Ok(Value::Array(values))
+}
+
+fn split(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = Path::new(matches.value_of("FILE").unwrap());
+    let dataset = Dataset::open(path)?;
+    let directory = Path::new(matches.value_of("DIRECTORY").unwrap());
+    let (geocode, precision) = parse_geocode(matches)?;
+    let min_coverage = matches.value_of("min_coverage")
+        .unwrap().parse::<f64>()?;
+
+    // split dataset along geocode windows
+    let window_bounds: Vec<Window> =
+        get_windows(&dataset, geocode, precision)?
+            .into_iter().map(|(_, window)| window).collect();
+
+    let options = SplitOptions { min_coverage, ..Default::default() };
+    let tiles = st_image::transform::split_all(&dataset,
+        &window_bounds, geocode.get_epsg_code(), &options)?;
+
+    // write tiles to directory
+    std::fs::create_dir_all(directory)?;
+    let driver = Driver::get("GTiff")?;
+
+    let mut values = Vec::new();
+    for tile in tiles {
+        let (min_cx, max_cx, min_cy, max_cy) = tile.window;
+        let code = geocode.get_code((min_cx + max_cx) / 2.0,
+            (min_cy + max_cy) / 2.0, precision)?;
+
+        let path = directory.join(format!("{}.tif", code));
+        let path = path.to_string_lossy();
+        tile.dataset.create_copy(&driver, &path)?;
+
+        values.push(json!({"code": code, "coverage": tile.coverage,
+            "path": path, "window": tile.window}));
+    }
+
+    Ok(Value::Array(values))
+}
+
+fn merge(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let datasets = open_datasets(matches)?;
+    let dataset = st_image::transform::merge(&datasets)?;
+    write_dataset(&dataset, matches.value_of("OUTPUT").unwrap())
+}
+
+fn fill(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let datasets = open_datasets(matches)?;
+    let dataset = st_image::fill(&datasets)?;
+    write_dataset(&dataset, matches.value_of("OUTPUT").unwrap())
+}
+
+fn coverage(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = matches.value_of("FILE").unwrap();
+    let dataset = Dataset::open(Path::new(path))?;
+    let coverage = st_image::get_coverage(&dataset)?;
+
+    Ok(json!({"coverage": coverage, "path": path}))
+}
+
+fn render(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = Path::new(matches.value_of("FILE").unwrap());
+    let dataset = Dataset::open(path)?;
+    let output = matches.value_of("OUTPUT").unwrap();
+
+    // parse render options
+    let stretch = parse_pair::<f64>(matches.value_of("stretch").unwrap())?;
+    let stretch = match matches.is_present("percentile") {
+        true => Stretch::Percentile(stretch.0, stretch.1),
+        false => Stretch::Linear(stretch.0, stretch.1),
+    };
+
+    let dimensions = match matches.value_of("size") {
+        Some(value) => Some(parse_pair::<usize>(value)?),
+        None => None,
+    };
+
+    let options = RenderOptions {
+        bands: parse_list(matches.value_of("bands").unwrap())?,
+        colormap: matches.value_of("colormap").map(|_| Colormap::Viridis),
+        dimensions,
+        gamma: matches.value_of("gamma").unwrap().parse::<f64>()?,
+        stretch,
+        ..Default::default()
+    };
+
+    // identify image format by output extension
+    let format = match Path::new(output).extension()
+            .and_then(|x| x.to_str()) {
+        Some("jpg") | Some("jpeg") => ImageFormat::Jpeg,
+        _ => ImageFormat::Png,
+    };
+
+    let data = st_image::render::render_image(&dataset, &options, format)?;
+    std::fs::write(output, data)?;
+
+    Ok(json!({"path": output}))
+}
+
+fn sample(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = Path::new(matches.value_of("FILE").unwrap());
+    let dataset = Dataset::open(path)?;
+    let epsg_code = matches.value_of("epsg_code").unwrap().parse::<u32>()?;
+    let interpolation = match matches.is_present("bilinear") {
+        true => Interpolation::Bilinear,
+        false => Interpolation::Nearest,
+    };
+
+    let points = matches.values_of("POINTS").unwrap()
+        .map(parse_pair::<f64>).collect::<Result<Vec<_>, _>>()?;
+    let samples = st_image::sample::sample(&dataset,
+        &points, epsg_code, interpolation)?;
+
+    // points outside the image have no values
+    let mut values = Vec::new();
+    for ((x, y), sample) in points.iter().zip(samples.iter()) {
+        let sample_values = match sample {
+            Some(sample) => sample.values.clone(),
+            None => Vec::new(),
+        };
+
+        values.push(json!({"x": x, "y": y, "values": sample_values}));
+    }
+
+    Ok(Value::Array(values))
+}
+
+fn encode(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = Path::new(matches.value_of("FILE").unwrap());
+    let dataset = Dataset::open(path)?;
+    let output = matches.value_of("OUTPUT").unwrap();
+
+    let mut writer = BufWriter::new(File::create(output)?);
+    st_image::serialize::write(&dataset, &mut writer)?;
+    writer.flush()?;
+
+    Ok(json!({"path": output}))
+}
+
+fn decode(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let file = File::open(matches.value_of("FILE").unwrap())?;
+    let mut reader = BufReader::new(file);
+    let dataset = st_image::serialize::read(&mut reader)?;
+
+    write_dataset(&dataset, matches.value_of("OUTPUT").unwrap())
+}
+
+fn zonal(matches: &ArgMatches) -> Result<Value, Box<dyn Error>> {
+    let path = Path::new(matches.value_of("FILE").unwrap());
+    let dataset = Dataset::open(path)?;
+    let epsg_code = matches.value_of("epsg_code").unwrap().parse::<u32>()?;
+    let percentiles =
+        parse_list::<f64>(matches.value_of("percentiles").unwrap())?;
+
+    let zones = st_image::zonal::read_zones(
+        matches.value_of("ZONES").unwrap(), matches.value_of("layer"),
+        matches.value_of("id_field"))?;
+    let statistics = st_image::zonal::get_zonal_statistics_epsg(
+        &dataset, &zones, epsg_code, &percentiles)?;
+
+    // report one row per zone and rasterband
+    let mut values = Vec::new();
+    for (id, zone_statistics) in statistics.iter() {
+        for (i, band_statistics) in zone_statistics.iter().enumerate() {
+            values.push(json!({"zone": id, "rasterband": i + 1,
+                "count": band_statistics.count,
+                "sum": band_statistics.sum,
+                "mean": band_statistics.mean,
+                "percentiles": band_statistics.percentiles}));
+        }
+    }
+
+    Ok(Value::Array(values))
+}
+
+fn get_windows(dataset: &Dataset, geocode: Geocode, precision: usize)
+        -> Result<Vec<(String, Window)>, Box<dyn Error>> {
+    // compute window boundaries over dataset bounds
+    let (x_interval, y_interval) = geocode.get_intervals(precision);
+    let (min_cx, max_cx, min_cy, max_cy) = st_image::coordinate
+        ::get_bounds_densified(dataset, geocode.get_epsg_code(),
+            st_image::coordinate::DENSIFY_POINTS, true)?;
+
+    let window_bounds = st_image::coordinate::get_windows(min_cx,
+        max_cx, min_cy, max_cy, x_interval, y_interval);
+
+    // label windows with geocode of their center
+    let mut windows = Vec::new();
+    for (min_cx, max_cx, min_cy, max_cy) in window_bounds {
+        let code = geocode.get_code((min_cx + max_cx) / 2.0,
+            (min_cy + max_cy) / 2.0, precision)?;
+        windows.push((code, (min_cx, max_cx, min_cy, max_cy)));
+    }
+
+    Ok(windows)
+}
+
+fn open_datasets(matches: &ArgMatches)
+        -> Result<Vec<Dataset>, Box<dyn Error>> {
+    let mut datasets = Vec::new();
+    for path in matches.values_of("FILES").unwrap() {
+        datasets.push(Dataset::open(Path::new(path))?);
+    }
+
+    Ok(datasets)
+}
+
+fn parse_geocode(matches: &ArgMatches)
+        -> Result<(Geocode, usize), Box<dyn Error>> {
+    let geocode = match matches.value_of("geocode").unwrap() {
+        "geohash" => Geocode::Geohash,
+        "quadtile" => Geocode::QuadTile,
+        geocode => return Err(format!("unsupported geocode '{}'",
+            geocode).into()),
+    };
+
+    let precision = matches.value_of("precision").unwrap().parse::<usize>()?;
+    Ok((geocode, precision))
+}
+
+fn parse_list<T: FromStr>(value: &str) -> Result<Vec<T>, Box<dyn Error>>
+        where T::Err: Error + 'static {
+    let mut values = Vec::new();
+    for field in value.split(',') {
+        values.push(field.trim().parse::<T>()?);
+    }
+
+    Ok(values)
+}
+
+fn parse_pair<T: Copy + FromStr>(value: &str)
+        -> Result<(T, T), Box<dyn Error>> where T::Err: Error + 'static {
+    match parse_list::<T>(value)?.as_slice() {
+        [first, second] => Ok((*first, *second)),
+        _ => Err(format!("expected 'a,b' pair, found '{}'", value).into()),
+    }
+}
+
+fn print_text(value: &Value) {
+    match value {
+        Value::Array(values) => values.iter().for_each(print_text),
+        Value::Object(map) => println!("{}", map.values()
+            .map(format_text).collect::<Vec<String>>().join("\t")),
+        value => println!("{}", format_text(value)),
+    }
+}
+
+fn format_text(value: &Value) -> String {
+    match value {
+        Value::Array(values) => values.iter()
+            .map(format_text).collect::<Vec<String>>().join(" "),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn write_dataset(dataset: &Dataset, path: &str)
+        -> Result<Value, Box<dyn Error>> {
+    let driver = Driver::get("GTiff")?;
+    dataset.create_copy(&driver, path)?;
+
+    let (width, height) = dataset.raster_size();
+    Ok(json!({"height": height, "path": path,
+        "rasterband_count": dataset.raster_count(), "width": width}))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    const FIXTURE: &str = "fixtures/MCD43A4.h10v04.006.tif";
+
+    fn execute(args: &[&str]) -> Value {
+        let matches = super::app()
+            .get_matches_from_safe(["st-image"].iter().chain(args.iter()))
+            .expect("parse arguments");
+
+        let (subcommand, sub_matches) = matches.subcommand();
+        super::execute(subcommand, sub_matches.expect("subcommand matches"))
+            .expect("execute subcommand")
+    }
+
+    #[test]
+    fn main_parse_pair() {
+        assert_eq!(super::parse_pair::<f64>("-105.5, 44").expect("parse"),
+            (-105.5, 44.0));
+        assert_eq!(super::parse_pair::<usize>("256,128").expect("parse"),
+            (256, 128));
+
+        assert!(super::parse_pair::<f64>("1").is_err());
+        assert!(super::parse_pair::<f64>("1,2,3").is_err());
+        assert!(super::parse_pair::<f64>("a,2").is_err());
+        assert!(super::parse_pair::<usize>("-1,2").is_err());
+    }
+
+    #[test]
+    fn main_format_text() {
+        assert_eq!(super::format_text(&json!("path")), "path");
+        assert_eq!(super::format_text(&json!(0.5)), "0.5");
+        assert_eq!(super::format_text(&json!([1, [2, 3]])), "1 2 3");
+    }
+
+    #[test]
+    fn main_json_output() {
+        // bounds report a single object
+        let value = execute(&["bounds", FIXTURE]);
+        let object = value.as_object().expect("bounds object");
+        for key in ["min_x", "max_x", "min_y", "max_y"].iter() {
+            assert!(object[*key].is_f64());
+        }
+
+        // coverage reports a single object
+        let value = execute(&["coverage", FIXTURE]);
+        assert!(value["coverage"].is_f64());
+        assert_eq!(value["path"], json!(FIXTURE));
+
+        // windows report an array of codes and windows
+        let value = execute(&["windows", FIXTURE, "-p", "2"]);
+        let values = value.as_array().expect("windows array");
+        assert!(!values.is_empty());
+        for value in values.iter() {
+            assert_eq!(value["code"].as_str().expect("code").len(), 2);
+            assert_eq!(value["window"].as_array()
+                .expect("window").len(), 4);
+        }
+
+        // samples report one entry per point, empty outside the image
+        let value = execute(&["sample", FIXTURE,
+            "-105.5,44.5", "0,0"]);
+        let values = value.as_array().expect("sample array");
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0]["x"], json!(-105.5));
+        assert_eq!(values[0]["y"], json!(44.5));
+        assert!(!values[0]["values"].as_array()
+            .expect("values").is_empty());
+        assert_eq!(values[1]["values"], json!([]));
+    }
+}
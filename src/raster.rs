@@ -0,0 +1,529 @@
+#[cfg(feature = "gdal-backend")]
+use gdal::{Dataset, Driver};
+#[cfg(feature = "gdal-backend")]
+use gdal::raster::{Buffer, GdalType};
+#[cfg(feature = "gdal-backend")]
+use gdal_sys::GDALDataType;
+
+use crate::FromPrimitive;
+use crate::coordinate::GeoTransform;
+
+use std::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DataType {
+    Byte,
+    UInt16,
+    Int16,
+    Float32,
+}
+
+impl DataType {
+    // codes match GDALDataType values to maintain serialized formats
+    pub fn from_code(code: u32) -> Result<DataType, Box<dyn Error>> {
+        match code {
+            1 => Ok(DataType::Byte),
+            2 => Ok(DataType::UInt16),
+            3 => Ok(DataType::Int16),
+            6 => Ok(DataType::Float32),
+            _ => Err(format!("unsupported data type '{}'", code).into()),
+        }
+    }
+
+    pub fn to_code(&self) -> u32 {
+        match self {
+            DataType::Byte => 1,
+            DataType::UInt16 => 2,
+            DataType::Int16 => 3,
+            DataType::Float32 => 6,
+        }
+    }
+}
+
+pub trait RasterType: Copy + FromPrimitive + PartialEq {
+    fn data_type() -> DataType;
+    fn from_any(raster: AnyRaster) -> Result<Raster<Self>, Box<dyn Error>>;
+    fn into_any(raster: Raster<Self>) -> AnyRaster;
+    fn to_f64(self) -> f64;
+}
+
+impl RasterType for u8 {
+    fn data_type() -> DataType {
+        DataType::Byte
+    }
+
+    fn from_any(raster: AnyRaster) -> Result<Raster<Self>, Box<dyn Error>> {
+        match raster {
+            AnyRaster::Byte(raster) => Ok(raster),
+            _ => Err("raster types do not match".into()),
+        }
+    }
+
+    fn into_any(raster: Raster<Self>) -> AnyRaster {
+        AnyRaster::Byte(raster)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl RasterType for u16 {
+    fn data_type() -> DataType {
+        DataType::UInt16
+    }
+
+    fn from_any(raster: AnyRaster) -> Result<Raster<Self>, Box<dyn Error>> {
+        match raster {
+            AnyRaster::UInt16(raster) => Ok(raster),
+            _ => Err("raster types do not match".into()),
+        }
+    }
+
+    fn into_any(raster: Raster<Self>) -> AnyRaster {
+        AnyRaster::UInt16(raster)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl RasterType for i16 {
+    fn data_type() -> DataType {
+        DataType::Int16
+    }
+
+    fn from_any(raster: AnyRaster) -> Result<Raster<Self>, Box<dyn Error>> {
+        match raster {
+            AnyRaster::Int16(raster) => Ok(raster),
+            _ => Err("raster types do not match".into()),
+        }
+    }
+
+    fn into_any(raster: Raster<Self>) -> AnyRaster {
+        AnyRaster::Int16(raster)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl RasterType for f32 {
+    fn data_type() -> DataType {
+        DataType::Float32
+    }
+
+    fn from_any(raster: AnyRaster) -> Result<Raster<Self>, Box<dyn Error>> {
+        match raster {
+            AnyRaster::Float32(raster) => Ok(raster),
+            _ => Err("raster types do not match".into()),
+        }
+    }
+
+    fn into_any(raster: Raster<Self>) -> AnyRaster {
+        AnyRaster::Float32(raster)
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Raster<T> {
+    bands: Vec<Vec<T>>,
+    height: usize,
+    no_data_value: Option<f64>,
+    projection: String,
+    transform: GeoTransform,
+    width: usize,
+}
+
+impl<T: RasterType> Raster<T> {
+    pub fn new(width: usize, height: usize, bands: Vec<Vec<T>>,
+            transform: GeoTransform, projection: String,
+            no_data_value: Option<f64>)
+            -> Result<Raster<T>, Box<dyn Error>> {
+        // validate band dimensions
+        if bands.iter().any(|x| x.len() != width * height) {
+            return Err("band length does not match dimensions".into());
+        }
+
+        Ok(Raster { bands, height, no_data_value,
+            projection, transform, width })
+    }
+
+    pub fn band(&self, index: usize) -> &[T] {
+        &self.bands[index]
+    }
+
+    pub fn band_mut(&mut self, index: usize) -> &mut [T] {
+        &mut self.bands[index]
+    }
+
+    pub fn bands(&self) -> &[Vec<T>] {
+        &self.bands
+    }
+
+    pub fn fill(rasters: &[Raster<T>])
+            -> Result<Raster<T>, Box<dyn Error>> {
+        let mut raster = match rasters.first() {
+            Some(raster) => raster.clone(),
+            None => return Err("no rasters to fill".into()),
+        };
+
+        let no_data_value = T::from_f64(
+            raster.no_data_value.unwrap_or(0.0));
+
+        // fill with remaining rasters
+        for fill_raster in rasters.iter().skip(1) {
+            // iterate over pixels
+            let size = raster.width * raster.height;
+            for i in 0..size {
+                if fill_raster.bands[0].len() <= i {
+                    break;
+                }
+
+                // check if raster pixel is valid
+                let valid = raster.bands.iter()
+                    .any(|x| x[i] != no_data_value);
+
+                // copy pixels from fill_raster bands
+                if !valid {
+                    for (band, fill_band) in raster.bands.iter_mut()
+                            .zip(fill_raster.bands.iter()) {
+                        band[i] = fill_band[i];
+                    }
+                }
+            }
+        }
+
+        Ok(raster)
+    }
+
+    pub fn geo_transform(&self) -> GeoTransform {
+        self.transform
+    }
+
+    pub fn get_coverage(&self) -> f64 {
+        let invalid_pixels = self.get_invalid_pixels();
+
+        // compute percentage of valid pixels
+        let pixel_count = (self.width * self.height) as f64;
+        let invalid_count = invalid_pixels.iter()
+            .filter(|x| **x).count() as f64;
+
+        (pixel_count - invalid_count) / pixel_count
+    }
+
+    pub fn get_invalid_pixels(&self) -> Vec<bool> {
+        let no_data_value = T::from_f64(
+            self.no_data_value.unwrap_or(0.0));
+
+        // pixels are invalid if all bands are no_data
+        let mut invalid_pixels = vec![true; self.width * self.height];
+        for band in self.bands.iter() {
+            for (i, pixel) in band.iter().enumerate() {
+                if *pixel != no_data_value {
+                    invalid_pixels[i] = false;
+                }
+            }
+        }
+
+        invalid_pixels
+    }
+
+    pub fn no_data_value(&self) -> Option<f64> {
+        self.no_data_value
+    }
+
+    pub fn projection(&self) -> &str {
+        &self.projection
+    }
+
+    pub fn raster_count(&self) -> usize {
+        self.bands.len()
+    }
+
+    pub fn raster_size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+#[cfg(feature = "gdal-backend")]
+impl<T: RasterType + GdalType> Raster<T> {
+    pub fn from_dataset(dataset: &Dataset)
+            -> Result<Raster<T>, Box<dyn Error>> {
+        // read rasterbands
+        let mut bands = Vec::new();
+        for i in 0..dataset.raster_count() {
+            let buffer = dataset.rasterband(i+1)?.read_band_as::<T>()?;
+            bands.push(buffer.data);
+        }
+
+        // read metadata, GCP datasets lack a transform
+        let (width, height) = dataset.raster_size();
+        let transform = dataset.geo_transform()
+            .map_err(|_| "dataset has no geotransform")?;
+        let transform = GeoTransform::from(transform);
+        let no_data_value = dataset.rasterband(1)?.no_data_value();
+
+        Raster::new(width, height, bands, transform,
+            dataset.projection(), no_data_value)
+    }
+
+    pub fn to_dataset(&self) -> Result<Dataset, Box<dyn Error>> {
+        // open memory dataset
+        let driver = Driver::get("Mem")?;
+        let dataset = crate::init_dataset(&driver, "unreachable",
+            T::gdal_type(), self.width as isize, self.height as isize,
+            self.bands.len() as isize, self.no_data_value)?;
+
+        dataset.set_geo_transform(&<[f64; 6]>::from(self.transform))?;
+        dataset.set_projection(&self.projection)?;
+
+        // set rasterbands
+        for (i, band) in self.bands.iter().enumerate() {
+            let buffer = Buffer::new((self.width, self.height),
+                band.clone());
+
+            dataset.rasterband((i+1) as isize)?.write::<T>((0, 0),
+                (self.width, self.height), &buffer)?;
+        }
+
+        Ok(dataset)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnyRaster {
+    Byte(Raster<u8>),
+    Int16(Raster<i16>),
+    UInt16(Raster<u16>),
+    Float32(Raster<f32>),
+}
+
+macro_rules! any_raster {
+    ($any_raster:expr, $raster:ident => $function:expr) => {
+        match $any_raster {
+            AnyRaster::Byte($raster) => $function,
+            AnyRaster::Int16($raster) => $function,
+            AnyRaster::UInt16($raster) => $function,
+            AnyRaster::Float32($raster) => $function,
+        }
+    };
+}
+
+impl AnyRaster {
+    pub fn data_type(&self) -> DataType {
+        match self {
+            AnyRaster::Byte(_) => DataType::Byte,
+            AnyRaster::Int16(_) => DataType::Int16,
+            AnyRaster::UInt16(_) => DataType::UInt16,
+            AnyRaster::Float32(_) => DataType::Float32,
+        }
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub fn from_dataset(dataset: &Dataset)
+            -> Result<AnyRaster, Box<dyn Error>> {
+        // rasters store a single data type for all rasterbands, so
+        //   mixed band types are promoted to Float32 which holds each
+        //   supported type exactly
+        let mut band_type = dataset.rasterband(1)?.band_type();
+        for i in 1..=dataset.raster_count() {
+            let rasterband_type = dataset.rasterband(i)?.band_type();
+            match rasterband_type {
+                GDALDataType::GDT_Byte | GDALDataType::GDT_Int16
+                    | GDALDataType::GDT_UInt16
+                    | GDALDataType::GDT_Float32 => (),
+                _ => return Err(format!(
+                    "unsupported band type '{}'", rasterband_type).into()),
+            }
+
+            if rasterband_type != band_type {
+                band_type = GDALDataType::GDT_Float32;
+            }
+        }
+
+        match band_type {
+            GDALDataType::GDT_Byte =>
+                Ok(AnyRaster::Byte(Raster::from_dataset(dataset)?)),
+            GDALDataType::GDT_Int16 =>
+                Ok(AnyRaster::Int16(Raster::from_dataset(dataset)?)),
+            GDALDataType::GDT_UInt16 =>
+                Ok(AnyRaster::UInt16(Raster::from_dataset(dataset)?)),
+            GDALDataType::GDT_Float32 =>
+                Ok(AnyRaster::Float32(Raster::from_dataset(dataset)?)),
+            band_type => Err(format!(
+                "unsupported band type '{}'", band_type).into()),
+        }
+    }
+
+    pub fn fill(rasters: &[AnyRaster])
+            -> Result<AnyRaster, Box<dyn Error>> {
+        // collect rasters of the first rasters type
+        macro_rules! fill {
+            ($variant:ident) => {{
+                let rasters = rasters.iter().map(|x| match x {
+                        AnyRaster::$variant(raster) => Ok(raster.clone()),
+                        _ => Err("raster types do not match"),
+                    }).collect::<Result<Vec<_>, _>>()?;
+
+                Ok(AnyRaster::$variant(Raster::fill(&rasters)?))
+            }};
+        }
+
+        match rasters.first() {
+            Some(AnyRaster::Byte(_)) => fill!(Byte),
+            Some(AnyRaster::Int16(_)) => fill!(Int16),
+            Some(AnyRaster::UInt16(_)) => fill!(UInt16),
+            Some(AnyRaster::Float32(_)) => fill!(Float32),
+            None => Err("no rasters to fill".into()),
+        }
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub fn gdal_type(&self) -> GDALDataType::Type {
+        match self {
+            AnyRaster::Byte(_) => GDALDataType::GDT_Byte,
+            AnyRaster::Int16(_) => GDALDataType::GDT_Int16,
+            AnyRaster::UInt16(_) => GDALDataType::GDT_UInt16,
+            AnyRaster::Float32(_) => GDALDataType::GDT_Float32,
+        }
+    }
+
+    pub fn geo_transform(&self) -> GeoTransform {
+        any_raster!(self, raster => raster.geo_transform())
+    }
+
+    pub fn get_coverage(&self) -> f64 {
+        any_raster!(self, raster => raster.get_coverage())
+    }
+
+    pub fn get_invalid_pixels(&self) -> Vec<bool> {
+        any_raster!(self, raster => raster.get_invalid_pixels())
+    }
+
+    pub fn no_data_value(&self) -> Option<f64> {
+        any_raster!(self, raster => raster.no_data_value())
+    }
+
+    pub fn projection(&self) -> &str {
+        any_raster!(self, raster => raster.projection())
+    }
+
+    pub fn raster_count(&self) -> usize {
+        any_raster!(self, raster => raster.raster_count())
+    }
+
+    pub fn raster_size(&self) -> (usize, usize) {
+        any_raster!(self, raster => raster.raster_size())
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    pub fn to_dataset(&self) -> Result<Dataset, Box<dyn Error>> {
+        any_raster!(self, raster => raster.to_dataset())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AnyRaster, Raster};
+    use crate::coordinate::GeoTransform;
+
+    const TRANSFORM: GeoTransform =
+        GeoTransform::new(0.0, 1.0, 0.0, 4.0, 0.0, -1.0);
+
+    #[test]
+    fn raster_coverage() {
+        let raster = Raster::<u16>::new(2, 2,
+            vec![vec![0, 1, 0, 0], vec![0, 0, 2, 0]],
+            TRANSFORM, String::new(), Some(0.0)).expect("new raster");
+
+        assert_eq!(raster.get_coverage(), 0.5);
+        assert_eq!(raster.get_invalid_pixels(),
+            vec![true, false, false, true]);
+    }
+
+    #[test]
+    fn raster_fill() {
+        let raster = AnyRaster::Int16(Raster::new(2, 2,
+            vec![vec![-1, 1, -1, -1], vec![-1, 1, 2, -1]],
+            TRANSFORM, String::new(), Some(-1.0)).expect("new raster"));
+        let fill_raster = AnyRaster::Int16(Raster::new(2, 2,
+            vec![vec![3, 3, 3, -1], vec![4, 4, 4, -1]],
+            TRANSFORM, String::new(), Some(-1.0)).expect("new raster"));
+
+        // fill raster and validate pixels
+        let result = AnyRaster::fill(&[raster, fill_raster])
+            .expect("fill rasters");
+        match result {
+            AnyRaster::Int16(raster) => {
+                assert_eq!(raster.band(0), &[3, 1, -1, -1]);
+                assert_eq!(raster.band(1), &[4, 1, 2, -1]);
+            },
+            _ => panic!("unexpected raster type"),
+        }
+
+        // validate mismatched types fail
+        let byte_raster = AnyRaster::Byte(Raster::new(1, 1, vec![vec![0]],
+            TRANSFORM, String::new(), None).expect("new raster"));
+        let int_raster = AnyRaster::Int16(Raster::new(1, 1, vec![vec![0]],
+            TRANSFORM, String::new(), None).expect("new raster"));
+        assert!(AnyRaster::fill(&[byte_raster, int_raster]).is_err());
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn raster_from_dataset() {
+        use gdal::Driver;
+        use gdal::raster::Buffer;
+        use super::DataType;
+        use gdal_sys::GDALDataType;
+
+        let raster = AnyRaster::Int16(Raster::new(2, 2,
+            vec![vec![-1, 1, 2, 3]], TRANSFORM, String::new(), Some(-1.0))
+            .expect("new raster"));
+
+        // validate dataset round trip
+        let dataset = raster.to_dataset().expect("to dataset");
+        assert_eq!(AnyRaster::from_dataset(&dataset)
+            .expect("from dataset"), raster);
+
+        // validate datasets without a geotransform fail
+        let driver = Driver::get("Mem").expect("get driver");
+        let dataset = driver.create_with_band_type::<i16>(
+            "", 2, 2, 1).expect("create dataset");
+        assert!(AnyRaster::from_dataset(&dataset).is_err());
+
+        // validate mixed band types are promoted to Float32
+        let dataset = driver.create_with_band_type::<u8>(
+            "", 2, 2, 1).expect("create dataset");
+        dataset.set_geo_transform(&<[f64; 6]>::from(TRANSFORM))
+            .expect("set transform");
+        unsafe {
+            gdal_sys::GDALAddBand(dataset.c_dataset(),
+                GDALDataType::GDT_Int16, std::ptr::null_mut());
+        }
+        dataset.rasterband(2).expect("rasterband").write::<i16>((0, 0),
+            (2, 2), &Buffer::new((2, 2), vec![-300, 0, 1, 300]))
+            .expect("write rasterband");
+
+        let raster = AnyRaster::from_dataset(&dataset)
+            .expect("from dataset");
+        assert_eq!(raster.data_type(), DataType::Float32);
+        match raster {
+            AnyRaster::Float32(raster) => assert_eq!(raster.band(1),
+                &[-300.0, 0.0, 1.0, 300.0][..]),
+            _ => panic!("raster is not Float32"),
+        }
+
+        // validate unsupported band types fail
+        let dataset = driver.create_with_band_type::<f64>(
+            "", 2, 2, 1).expect("create dataset");
+        assert!(AnyRaster::from_dataset(&dataset).is_err());
+    }
+}
@@ -0,0 +1,371 @@
+use gdal::{Dataset, Driver};
+use gdal::raster::Buffer;
+use gdal_sys::GDALDataType;
+
+use crate::raster::{AnyRaster, Raster, RasterType};
+use crate::transform::Resampling;
+
+use std::error::Error;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static VSIMEM_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+const VIRIDIS: [(u8, u8, u8); 5] = [(68, 1, 84), (59, 82, 139),
+    (33, 145, 140), (94, 201, 98), (253, 231, 37)];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Colormap {
+    Custom(Vec<(u8, u8, u8)>),
+    Viridis,
+}
+
+impl Colormap {
+    fn get_color(&self, value: f64) -> (u8, u8, u8) {
+        let colors = match self {
+            Colormap::Custom(colors) => colors.as_slice(),
+            Colormap::Viridis => &VIRIDIS,
+        };
+
+        // linearly interpolate between neighboring colors
+        let position = value * (colors.len() - 1) as f64;
+        let index = (position.floor() as usize).min(colors.len() - 1);
+        let (start, end) = (colors[index],
+            colors[(index + 1).min(colors.len() - 1)]);
+
+        let weight = position - index as f64;
+        let interpolate = |a: u8, b: u8|
+            (a as f64 + ((b as f64 - a as f64) * weight)).round() as u8;
+
+        (interpolate(start.0, end.0), interpolate(start.1, end.1),
+            interpolate(start.2, end.2))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stretch {
+    Linear(f64, f64),
+    Percentile(f64, f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    pub bands: Vec<usize>,
+    pub colormap: Option<Colormap>,
+    pub dimensions: Option<(usize, usize)>,
+    pub gamma: f64,
+    pub resampling: Resampling,
+    pub stretch: Stretch,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            bands: vec![1, 2, 3],
+            colormap: None,
+            dimensions: None,
+            gamma: 1.0,
+            resampling: Resampling::Nearest,
+            stretch: Stretch::Linear(0.0, 255.0),
+        }
+    }
+}
+
+pub fn render(raster: &AnyRaster, options: &RenderOptions)
+        -> Result<Raster<u8>, Box<dyn Error>> {
+    match raster {
+        AnyRaster::Byte(raster) => _render(raster, options),
+        AnyRaster::Int16(raster) => _render(raster, options),
+        AnyRaster::UInt16(raster) => _render(raster, options),
+        AnyRaster::Float32(raster) => _render(raster, options),
+    }
+}
+
+fn _render<T: RasterType>(raster: &Raster<T>, options: &RenderOptions)
+        -> Result<Raster<u8>, Box<dyn Error>> {
+    // validate band combination
+    if options.bands.len() != 1 && options.bands.len() != 3 {
+        return Err("render requires one or three bands".into());
+    }
+
+    if options.bands.iter().any(|x| *x == 0 || *x > raster.raster_count()) {
+        return Err("render band index out of range".into());
+    }
+
+    if options.colormap.is_some() && options.bands.len() != 1 {
+        return Err("colormap requires a single band".into());
+    }
+
+    if options.gamma <= 0.0 {
+        return Err("gamma must be positive".into());
+    }
+
+    if let Some(Colormap::Custom(colors)) = &options.colormap {
+        if colors.is_empty() {
+            return Err("colormap requires at least one color".into());
+        }
+    }
+
+    // pixels are invalid if all rendered bands are no_data
+    let (width, height) = raster.raster_size();
+    let no_data_value = T::from_f64(raster.no_data_value().unwrap_or(0.0));
+    let mut invalid_pixels = vec![true; width * height];
+    for band in options.bands.iter() {
+        for (i, pixel) in raster.band(band - 1).iter().enumerate() {
+            if *pixel != no_data_value {
+                invalid_pixels[i] = false;
+            }
+        }
+    }
+
+    // stretch bands to [0, 1] over valid pixels
+    let values: Vec<Vec<f64>> = options.bands.iter()
+        .map(|x| stretch_band(raster.band(x - 1), &invalid_pixels,
+            &options.stretch, options.gamma)).collect();
+
+    // map to 8-bit through colormap or grayscale replication
+    let mut bands = match (&options.colormap, values.len()) {
+        (Some(colormap), _) => {
+            let colors: Vec<(u8, u8, u8)> = values[0].iter()
+                .map(|x| colormap.get_color(*x)).collect();
+
+            vec![colors.iter().map(|x| x.0).collect(),
+                colors.iter().map(|x| x.1).collect(),
+                colors.iter().map(|x| x.2).collect()]
+        },
+        (None, 1) => vec![to_byte_band(&values[0]); 3],
+        (None, _) => values.iter().map(|x| to_byte_band(x)).collect(),
+    };
+
+    // set alpha transparent for no_data pixels
+    let alpha = invalid_pixels.iter()
+        .map(|x| match x {
+            true => 0,
+            false => 255,
+        }).collect();
+    bands.push(alpha);
+
+    Raster::new(width, height, bands, raster.geo_transform(),
+        raster.projection().to_string(), None)
+}
+
+pub fn render_dataset(dataset: &Dataset, options: &RenderOptions)
+        -> Result<Raster<u8>, Box<dyn Error>> {
+    let raster = match options.dimensions {
+        Some((width, height)) => {
+            let resample_dataset = crate::transform::resample(dataset,
+                width, height, options.resampling)?;
+            AnyRaster::from_dataset(&resample_dataset)?
+        },
+        None => AnyRaster::from_dataset(dataset)?,
+    };
+
+    render(&raster, options)
+}
+
+pub fn render_image(dataset: &Dataset, options: &RenderOptions,
+        format: ImageFormat) -> Result<Vec<u8>, Box<dyn Error>> {
+    let raster = render_dataset(dataset, options)?;
+    match format {
+        ImageFormat::Jpeg => {
+            // jpeg does not support an alpha band
+            let (width, height) = raster.raster_size();
+            let bands = raster.bands()[..3].to_vec();
+            let raster = Raster::new(width, height, bands,
+                raster.geo_transform(), raster.projection().to_string(),
+                None)?;
+
+            encode(&raster, "JPEG", "jpg")
+        },
+        ImageFormat::Png => encode(&raster, "PNG", "png"),
+    }
+}
+
+pub fn render_png(dataset: &Dataset, options: &RenderOptions)
+        -> Result<Vec<u8>, Box<dyn Error>> {
+    render_image(dataset, options, ImageFormat::Png)
+}
+
+fn encode(raster: &Raster<u8>, driver_name: &str, extension: &str)
+        -> Result<Vec<u8>, Box<dyn Error>> {
+    // write image to a unique in-memory file
+    let filename = format!("/vsimem/st-image-{}-{}.{}", std::process::id(),
+        VSIMEM_COUNT.fetch_add(1, Ordering::SeqCst), extension);
+
+    // copy bands without georeferencing so no PAM sidecar is written
+    let (width, height) = raster.raster_size();
+    let dataset = crate::init_dataset(&Driver::get("Mem")?, "unreachable",
+        GDALDataType::GDT_Byte, width as isize, height as isize,
+        raster.raster_count() as isize, None)?;
+
+    for (i, band) in raster.bands().iter().enumerate() {
+        let buffer = Buffer::new((width, height), band.clone());
+        dataset.rasterband((i+1) as isize)?.write::<u8>((0, 0),
+            (width, height), &buffer)?;
+    }
+
+    // closing the copy flushes the in-memory file
+    let driver = Driver::get(driver_name)?;
+    let result = dataset.create_copy(&driver, &filename).map(|_| ());
+
+    // take ownership of in-memory file buffer and remove any sidecar
+    let c_filename = CString::new(filename.as_str())?;
+    let c_aux_filename = CString::new(format!("{}.aux.xml", filename))?;
+    let mut length = 0;
+    let data = unsafe {
+        let buffer = gdal_sys::VSIGetMemFileBuffer(
+            c_filename.as_ptr(), &mut length, 1);
+        gdal_sys::VSIUnlink(c_aux_filename.as_ptr());
+
+        match buffer.is_null() {
+            true => None,
+            false => {
+                let data = std::slice::from_raw_parts(buffer,
+                    length as usize).to_vec();
+                gdal_sys::VSIFree(buffer as *mut std::os::raw::c_void);
+                Some(data)
+            },
+        }
+    };
+
+    result?;
+    data.ok_or_else(|| "failed to encode image".into())
+}
+
+fn stretch_band<T: RasterType>(band: &[T], invalid_pixels: &[bool],
+        stretch: &Stretch, gamma: f64) -> Vec<f64> {
+    let (min, max) = match stretch {
+        Stretch::Linear(min, max) => (*min, *max),
+        Stretch::Percentile(min, max) => {
+            // compute percentile values over valid pixels
+            let mut values: Vec<f64> = band.iter().zip(invalid_pixels)
+                .filter(|(_, invalid)| !**invalid)
+                .map(|(x, _)| x.to_f64()).collect();
+            values.sort_by(|a, b| a.partial_cmp(b)
+                .unwrap_or(std::cmp::Ordering::Equal));
+
+            match values.is_empty() {
+                true => (0.0, 1.0),
+                false => (crate::percentile(&values, *min),
+                    crate::percentile(&values, *max)),
+            }
+        },
+    };
+
+    let range = (max - min).max(f64::EPSILON);
+    band.iter().map(|x| ((x.to_f64() - min) / range).clamp(0.0, 1.0))
+        .map(|x| x.powf(1.0 / gamma)).collect()
+}
+
+fn to_byte_band(values: &[f64]) -> Vec<u8> {
+    values.iter().map(|x| (x * 255.0).round() as u8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Colormap, RenderOptions, Stretch};
+    use crate::coordinate::GeoTransform;
+    use crate::raster::{AnyRaster, Raster};
+
+    use std::ffi::CString;
+
+    #[test]
+    fn render_bands() {
+        let raster = AnyRaster::UInt16(Raster::new(2, 2,
+            vec![vec![0, 100, 200, 300], vec![50, 200, 0, 0]],
+            GeoTransform::new(0.0, 1.0, 0.0, 2.0, 0.0, -1.0),
+            String::new(),
+            Some(0.0)).expect("new raster"));
+
+        // render single band as grayscale with no_data transparency
+        //   masked by the rendered band only
+        let options = RenderOptions {
+            bands: vec![1],
+            stretch: Stretch::Linear(100.0, 200.0),
+            ..Default::default()
+        };
+
+        let result = super::render(&raster, &options).expect("render");
+        assert_eq!(result.raster_count(), 4);
+        for i in 0..3 {
+            assert_eq!(result.band(i), &[0, 0, 255, 255]);
+        }
+        assert_eq!(result.band(3), &[0, 255, 255, 255]);
+
+        // validate band combinations
+        let options = RenderOptions { bands: vec![1, 2], ..options };
+        assert!(super::render(&raster, &options).is_err());
+
+        let options = RenderOptions { bands: vec![1, 2, 3], ..options };
+        assert!(super::render(&raster, &options).is_err());
+    }
+
+    #[test]
+    fn render_stretch() {
+        let raster = AnyRaster::Float32(Raster::new(5, 1,
+            vec![vec![-1.0, 0.0, 0.25, 1.0, 100.0]],
+            GeoTransform::new(0.0, 1.0, 0.0, 1.0, 0.0, -1.0),
+            String::new(),
+            Some(-1.0)).expect("new raster"));
+
+        // percentile stretch ignores no_data pixels
+        let options = RenderOptions {
+            bands: vec![1],
+            stretch: Stretch::Percentile(0.0, 75.0),
+            ..Default::default()
+        };
+
+        let result = super::render(&raster, &options).expect("render");
+        assert_eq!(result.band(0), &[0, 0, 64, 255, 255]);
+
+        // gamma brightens midtones
+        let options = RenderOptions { gamma: 2.0, ..options };
+        let result = super::render(&raster, &options).expect("render");
+        assert_eq!(result.band(0), &[0, 0, 128, 255, 255]);
+
+        // colormap maps stretched values to colors
+        let options = RenderOptions {
+            colormap: Some(Colormap::Custom(vec![(0, 0, 0), (0, 100, 200)])),
+            gamma: 1.0,
+            ..options
+        };
+
+        let result = super::render(&raster, &options).expect("render");
+        assert_eq!(result.band(0), &[0, 0, 0, 0, 0]);
+        assert_eq!(result.band(1), &[0, 0, 25, 100, 100]);
+        assert_eq!(result.band(2), &[0, 0, 50, 200, 200]);
+        assert_eq!(result.band(3), &[0, 255, 255, 255, 255]);
+
+        let options = RenderOptions { bands: vec![1, 1, 1], ..options };
+        assert!(super::render(&raster, &options).is_err());
+    }
+
+    #[test]
+    fn render_encode() {
+        let vsimem_count = || unsafe {
+            let c_path = CString::new("/vsimem/").expect("path");
+            let files = gdal_sys::VSIReadDir(c_path.as_ptr());
+            let count = gdal_sys::CSLCount(
+                files as gdal_sys::CSLConstList);
+            gdal_sys::CSLDestroy(files);
+            count
+        };
+
+        let raster = Raster::<u8>::new(2, 2, vec![vec![0, 64, 128, 255]; 4],
+            GeoTransform::new(0.0, 1.0, 0.0, 2.0, 0.0, -1.0),
+            "projection".to_string(),
+            None).expect("new raster");
+
+        // encoding leaves no in-memory files behind
+        let count = vsimem_count();
+        let data = super::encode(&raster, "PNG", "png").expect("encode png");
+        assert_eq!(&data[..4], b"\x89PNG");
+        assert_eq!(vsimem_count(), count);
+    }
+}
@@ -0,0 +1,330 @@
+#[cfg(feature = "gdal-backend")]
+use gdal::Dataset;
+#[cfg(feature = "gdal-backend")]
+use gdal::raster::GdalType;
+#[cfg(feature = "gdal-backend")]
+use gdal::spatial_ref::CoordTransform;
+#[cfg(feature = "gdal-backend")]
+use gdal_sys::GDALDataType;
+
+use crate::raster::{AnyRaster, Raster, RasterType};
+
+use std::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Interpolation {
+    Bilinear,
+    Nearest,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub pixel: (f64, f64),
+    pub values: Vec<Option<f64>>,
+}
+
+impl Sample {
+    pub fn valid_count(&self) -> usize {
+        self.values.iter().filter(|x| x.is_some()).count()
+    }
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn sample(dataset: &Dataset, points: &[(f64, f64)], epsg_code: u32,
+        interpolation: Interpolation)
+        -> Result<Vec<Option<Sample>>, Box<dyn Error>> {
+    // convert points into dataset pixel space
+    let (transform, _, src_spatial_ref, dst_spatial_ref) =
+        crate::coordinate::get_transform_refs(dataset, epsg_code)?;
+    let reverse_transform = CoordTransform::new(
+        &dst_spatial_ref, &src_spatial_ref)?;
+
+    // points failing to transform fall outside the image
+    let (width, height) = dataset.raster_size();
+    let (xs, ys): (Vec<f64>, Vec<f64>) = points.iter().cloned().unzip();
+    let pixels: Vec<Option<(f64, f64)>> =
+        crate::coordinate::coords_to_pixels(&xs, &ys,
+            &transform, &reverse_transform)?.into_iter()
+        .map(|x| x.filter(|(px, py)| *px >= 0.0 && *px < width as f64
+            && *py >= 0.0 && *py < height as f64))
+        .collect();
+
+    // sample each rasterband reading only the surrounding pixels
+    let mut values = vec![Vec::new(); points.len()];
+    for i in 0..dataset.raster_count() {
+        let band_values = match dataset.rasterband(i+1)?.band_type() {
+            GDALDataType::GDT_Byte => _sample_band::<u8>(dataset,
+                i+1, &pixels, interpolation)?,
+            GDALDataType::GDT_Int16 => _sample_band::<i16>(dataset,
+                i+1, &pixels, interpolation)?,
+            GDALDataType::GDT_UInt16 => _sample_band::<u16>(dataset,
+                i+1, &pixels, interpolation)?,
+            GDALDataType::GDT_Float32 => _sample_band::<f32>(dataset,
+                i+1, &pixels, interpolation)?,
+            band_type => return Err(format!(
+                "unsupported band type '{}'", band_type).into()),
+        };
+
+        for (point_values, value) in values.iter_mut().zip(band_values) {
+            point_values.push(value);
+        }
+    }
+
+    Ok(pixels.into_iter().zip(values)
+        .map(|(pixel, values)| pixel.map(|pixel| Sample { pixel, values }))
+        .collect())
+}
+
+#[cfg(feature = "gdal-backend")]
+fn _sample_band<T: RasterType + GdalType>(dataset: &Dataset, index: isize,
+        pixels: &[Option<(f64, f64)>], interpolation: Interpolation)
+        -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+    let rasterband = dataset.rasterband(index)?;
+    let no_data_value = rasterband.no_data_value().map(T::from_f64);
+    let size = dataset.raster_size();
+
+    let mut values = Vec::new();
+    for pixel in pixels.iter() {
+        let (px, py) = match pixel {
+            Some(pixel) => *pixel,
+            None => {
+                values.push(None);
+                continue;
+            },
+        };
+
+        // read the window of pixels used by the interpolation
+        let (offset, window_size) =
+            get_sample_window(size, px, py, interpolation);
+        let buffer = rasterband.read_as::<T>((offset.0 as isize,
+            offset.1 as isize), window_size, window_size)?;
+
+        values.push(get_value(&buffer.data, window_size,
+            px - offset.0 as f64, py - offset.1 as f64,
+            no_data_value, interpolation));
+    }
+
+    Ok(values)
+}
+
+pub fn sample_raster(raster: &AnyRaster, points: &[(f64, f64)],
+        interpolation: Interpolation)
+        -> Result<Vec<Option<Sample>>, Box<dyn Error>> {
+    // convert points to pixel space through the inverse transform
+    let (xs, ys): (Vec<f64>, Vec<f64>) = points.iter().cloned().unzip();
+    let (pixel_xs, pixel_ys) = crate::coordinate::invert_coords(
+        &xs, &ys, &raster.geo_transform())?;
+
+    let pixels: Vec<(f64, f64)> =
+        pixel_xs.into_iter().zip(pixel_ys).collect();
+    Ok(sample_pixels(raster, &pixels, interpolation))
+}
+
+fn sample_pixels(raster: &AnyRaster, pixels: &[(f64, f64)],
+        interpolation: Interpolation) -> Vec<Option<Sample>> {
+    match raster {
+        AnyRaster::Byte(raster) =>
+            _sample_pixels(raster, pixels, interpolation),
+        AnyRaster::Int16(raster) =>
+            _sample_pixels(raster, pixels, interpolation),
+        AnyRaster::UInt16(raster) =>
+            _sample_pixels(raster, pixels, interpolation),
+        AnyRaster::Float32(raster) =>
+            _sample_pixels(raster, pixels, interpolation),
+    }
+}
+
+fn _sample_pixels<T: RasterType>(raster: &Raster<T>,
+        pixels: &[(f64, f64)], interpolation: Interpolation)
+        -> Vec<Option<Sample>> {
+    let (width, height) = raster.raster_size();
+    let no_data_value = raster.no_data_value().map(T::from_f64);
+
+    let mut samples = Vec::new();
+    for (px, py) in pixels.iter().cloned() {
+        if !(px >= 0.0 && px < width as f64
+                && py >= 0.0 && py < height as f64) {
+            samples.push(None);
+            continue;
+        }
+
+        // read all rasterbands reporting no_data pixels as None
+        let values = raster.bands().iter().map(|band| get_value(band,
+            (width, height), px, py, no_data_value, interpolation))
+            .collect();
+
+        samples.push(Some(Sample { pixel: (px, py), values }));
+    }
+
+    samples
+}
+
+#[cfg(feature = "gdal-backend")]
+fn get_sample_window(size: (usize, usize), px: f64, py: f64,
+        interpolation: Interpolation)
+        -> ((usize, usize), (usize, usize)) {
+    let (width, height) = size;
+    match interpolation {
+        Interpolation::Bilinear => {
+            // surrounding pixel centers clamped to the image edges
+            let min_x = (px - 0.5).floor() as isize;
+            let min_y = (py - 0.5).floor() as isize;
+            let clamp_x = |x: isize| x.clamp(0, width as isize - 1) as usize;
+            let clamp_y = |y: isize| y.clamp(0, height as isize - 1) as usize;
+
+            let (min_col, max_col) = (clamp_x(min_x), clamp_x(min_x + 1));
+            let (min_row, max_row) = (clamp_y(min_y), clamp_y(min_y + 1));
+            ((min_col, min_row),
+                (max_col - min_col + 1, max_row - min_row + 1))
+        },
+        Interpolation::Nearest => ((px as usize, py as usize), (1, 1)),
+    }
+}
+
+fn get_value<T: RasterType>(band: &[T], size: (usize, usize),
+        px: f64, py: f64, no_data_value: Option<T>,
+        interpolation: Interpolation) -> Option<f64> {
+    match interpolation {
+        Interpolation::Bilinear =>
+            get_bilinear(band, size, px, py, no_data_value),
+        Interpolation::Nearest => {
+            let pixel = band[(py as usize * size.0) + px as usize];
+            match Some(pixel) != no_data_value {
+                true => Some(pixel.to_f64()),
+                false => None,
+            }
+        },
+    }
+}
+
+fn get_bilinear<T: RasterType>(band: &[T], size: (usize, usize),
+        px: f64, py: f64, no_data_value: Option<T>) -> Option<f64> {
+    let (width, height) = size;
+
+    // interpolate between the surrounding pixel centers
+    let (x, y) = (px - 0.5, py - 0.5);
+    let (min_x, min_y) = (x.floor(), y.floor());
+    let (x_weight, y_weight) = (x - min_x, y - min_y);
+
+    let neighbors = [(0, 0, (1.0 - x_weight) * (1.0 - y_weight)),
+        (1, 0, x_weight * (1.0 - y_weight)),
+        (0, 1, (1.0 - x_weight) * y_weight),
+        (1, 1, x_weight * y_weight)];
+
+    // weight valid neighbors clamped to the image edges
+    let (mut value, mut weight) = (0.0, 0.0);
+    for (x_offset, y_offset, pixel_weight) in neighbors.iter() {
+        let col = (min_x as isize + x_offset)
+            .clamp(0, width as isize - 1) as usize;
+        let row = (min_y as isize + y_offset)
+            .clamp(0, height as isize - 1) as usize;
+
+        let pixel = band[(row * width) + col];
+        if *pixel_weight > 0.0 && Some(pixel) != no_data_value {
+            value += pixel.to_f64() * pixel_weight;
+            weight += pixel_weight;
+        }
+    }
+
+    match weight > 0.0 {
+        true => Some(value / weight),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interpolation, Sample};
+    use crate::coordinate::GeoTransform;
+    use crate::raster::{AnyRaster, Raster};
+
+    #[test]
+    fn sample_raster() {
+        let raster = AnyRaster::Float32(Raster::new(3, 2,
+            vec![vec![1.0, 2.0, 3.0, 4.0, 5.0, -1.0],
+                vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0]],
+            GeoTransform::new(100.0, 10.0, 0.0, 200.0, 0.0, -10.0),
+            String::new(),
+            Some(-1.0)).expect("new raster"));
+
+        let points = [(105.0, 195.0), (120.0, 190.0),
+            (125.0, 185.0), (95.0, 195.0)];
+
+        // nearest reports no_data values and points outside the image
+        let samples = super::sample_raster(&raster, &points,
+            Interpolation::Nearest).expect("sample raster");
+        assert_eq!(samples[0], Some(Sample {
+            pixel: (0.5, 0.5),
+            values: vec![Some(1.0), Some(10.0)],
+        }));
+        assert_eq!(samples[2].as_ref().expect("sample").values,
+            vec![None, Some(60.0)]);
+        assert_eq!(samples[3], None);
+
+        // bilinear weights valid neighboring pixel centers
+        let samples = super::sample_raster(&raster, &points,
+            Interpolation::Bilinear).expect("sample raster");
+        assert_eq!(samples[0].as_ref().expect("sample").values,
+            vec![Some(1.0), Some(10.0)]);
+        assert_eq!(samples[1].as_ref().expect("sample").values,
+            vec![Some(10.0 / 3.0), Some(40.0)]);
+        assert_eq!(samples[2].as_ref().expect("sample").values,
+            vec![None, Some(60.0)]);
+
+        // rasters without a no_data value report zero pixels
+        let raster = AnyRaster::Byte(Raster::new(2, 1, vec![vec![0, 4]],
+            GeoTransform::new(0.0, 1.0, 0.0, 1.0, 0.0, -1.0),
+            String::new(), None)
+            .expect("new raster"));
+        let samples = super::sample_raster(&raster, &[(0.5, 0.5),
+            (1.0, 0.5)], Interpolation::Bilinear).expect("sample raster");
+        assert_eq!(samples[0].as_ref().expect("sample").values,
+            vec![Some(0.0)]);
+        assert_eq!(samples[1].as_ref().expect("sample").values,
+            vec![Some(2.0)]);
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn sample_dataset() {
+        use gdal::Dataset;
+        use std::path::Path;
+
+        // read dataset warped into epsg 4326
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+        let options = crate::transform::SplitOptions {
+            dimensions: Some((64, 32)),
+            ..Default::default()
+        };
+
+        let dataset = crate::transform::split_tile(&dataset, -106.0,
+            -105.0, 44.0, 45.0, 4326, &options)
+            .expect("split tile").expect("tile").dataset;
+        let raster = AnyRaster::from_dataset(&dataset).expect("raster");
+        let transform = raster.geo_transform();
+
+        // windowed reads match sampling the full raster
+        let (width, height) = raster.raster_size();
+        let points: Vec<(f64, f64)> = [(0.25, 0.25), (10.5, 20.75),
+                (width as f64 - 0.25, height as f64 - 0.25),
+                (width as f64 + 1.0, 0.5)].iter()
+            .map(|(x, y)| transform.pixel_to_world(*x, *y)).collect();
+
+        for interpolation in [Interpolation::Bilinear,
+                Interpolation::Nearest].iter() {
+            let samples = super::sample_raster(&raster, &points,
+                *interpolation).expect("sample raster");
+            let dataset_samples = super::sample(&dataset, &points,
+                4326, *interpolation).expect("sample dataset");
+
+            assert_eq!(dataset_samples.len(), points.len());
+            assert!(dataset_samples[3].is_none());
+            for (sample, dataset_sample) in samples.iter()
+                    .zip(dataset_samples.iter()).take(3) {
+                assert_eq!(sample.as_ref().expect("sample").values,
+                    dataset_sample.as_ref().expect("sample").values);
+            }
+        }
+    }
+}
@@ -1,22 +1,34 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-use gdal::{Dataset, Driver};
-use gdal::raster::Buffer;
-use gdal_sys::GDALDataType;
+#[cfg(feature = "gdal-backend")]
+use gdal::Dataset;
+
+use crate::backend::Backend;
+use crate::coordinate::GeoTransform;
+use crate::raster::{AnyRaster, DataType, Raster, RasterType};
 
 use std::error::Error;
 use std::io::{Read, Write};
 
+#[cfg(feature = "gdal-backend")]
 pub fn read<T: Read>(reader: &mut T)
         -> Result<Dataset, Box<dyn Error>> {
+    let raster = read_raster(reader)?;
+    Dataset::from_raster(raster)
+}
+
+pub fn read_raster<T: Read>(reader: &mut T)
+        -> Result<AnyRaster, Box<dyn Error>> {
     // read image dimensions
-    let width = reader.read_u32::<BigEndian>()? as isize;
-    let height = reader.read_u32::<BigEndian>()? as isize;
+    let width = reader.read_u32::<BigEndian>()? as usize;
+    let height = reader.read_u32::<BigEndian>()? as usize;
 
     // read geo transform
     let mut transform = [0.0f64; 6];
     for value in transform.iter_mut() {
         *value = reader.read_f64::<BigEndian>()?;
     }
+
+    let transform = GeoTransform::from(transform);
  
     // read projection
     let projection_len = reader.read_u32::<BigEndian>()?;
@@ -24,118 +36,91 @@ pub fn read<T: Read>(reader: &mut T)
     reader.read_exact(&mut projection_buf)?;
     let projection = String::from_utf8(projection_buf)?;
 
-    // read gdal type and no_data value
-    let gdal_type = reader.read_u32::<BigEndian>()?;
+    // read data type and no_data value
+    let data_type = DataType::from_code(reader.read_u32::<BigEndian>()?)?;
     let no_data_value = match reader.read_u8()? {
         0 => None,
         _ => Some(reader.read_f64::<BigEndian>()?),
     };
  
     // read rasterband count
-    let rasterband_count = reader.read_u8()? as isize;
-
-    // initialize dataset
-    let driver = Driver::get("Mem")?;
-    let dataset = crate::init_dataset(&driver, "unreachable", gdal_type,
-        width, height, rasterband_count, no_data_value)?;
+    let rasterband_count = reader.read_u8()? as usize;
 
-    dataset.set_geo_transform(&transform)?;
-    dataset.set_projection(&projection)?;
- 
     // read rasterbands
-    for i in 0..rasterband_count {
-        read_raster(&dataset, i+1, reader)?;
-    }
+    let size = width * height;
+    let raster = match data_type {
+        DataType::Byte => AnyRaster::Byte(Raster::new(width, height,
+            read_rasters(reader, rasterband_count, size,
+                |x| x.read_u8())?,
+            transform, projection, no_data_value)?),
+        DataType::Int16 => AnyRaster::Int16(Raster::new(width, height,
+            read_rasters(reader, rasterband_count, size,
+                |x| x.read_i16::<BigEndian>())?,
+            transform, projection, no_data_value)?),
+        DataType::UInt16 => AnyRaster::UInt16(Raster::new(width, height,
+            read_rasters(reader, rasterband_count, size,
+                |x| x.read_u16::<BigEndian>())?,
+            transform, projection, no_data_value)?),
+        DataType::Float32 => AnyRaster::Float32(Raster::new(width, height,
+            read_rasters(reader, rasterband_count, size,
+                |x| x.read_f32::<BigEndian>())?,
+            transform, projection, no_data_value)?),
+    };
 
-    Ok(dataset)
+    Ok(raster)
 }
 
-fn read_raster<T: Read>(dataset: &Dataset, index: isize,
-        reader: &mut T) -> Result<(), Box<dyn Error>> {
-    // compute raster size
-    let (width, height) = dataset.raster_size();
-    let size = (width * height) as usize;
-
-    // read raster type
-    let gdal_type = reader.read_u32::<BigEndian>()?;
-    match gdal_type  {
-        GDALDataType::GDT_Byte => {
-            let mut data = vec![0u8; size];
-            reader.read_exact(&mut data)?;
+fn read_rasters<T: Read, U: RasterType>(reader: &mut T,
+        rasterband_count: usize, size: usize,
+        read_pixel: impl Fn(&mut T) -> std::io::Result<U>)
+        -> Result<Vec<Vec<U>>, Box<dyn Error>> {
+    let mut rasters = Vec::new();
+    for _ in 0..rasterband_count {
+        // read raster type
+        let data_type = DataType::from_code(reader.read_u32::<BigEndian>()?)?;
+        if data_type != U::data_type() {
+            return Err("rasterband data types do not match".into());
+        }
 
-            let buffer = Buffer::new((width as usize,
-                height as usize), data);
+        // read rasterband
+        let mut data = Vec::with_capacity(size);
+        for _ in 0..size {
+            data.push(read_pixel(reader)?);
+        }
 
-            dataset.rasterband(index)?.write::<u8>((0, 0),
-                (width as usize, height as usize), &buffer)?;
-        },
-        GDALDataType::GDT_Int16 => {
-            // read rasterband
-            let mut data = Vec::new();
-            for _ in 0..size {
-                data.push(reader.read_i16::<BigEndian>()?);
-            }
-
-            let buffer = Buffer::new((width as usize,
-                height as usize), data);
-
-            dataset.rasterband(index)?.write::<i16>((0, 0),
-                (width as usize, height as usize), &buffer)?;
-        },
-        GDALDataType::GDT_UInt16 => {
-            // read rasterband
-            let mut data = Vec::new();
-            for _ in 0..size {
-                data.push(reader.read_u16::<BigEndian>()?);
-            }
-
-            let buffer = Buffer::new((width as usize,
-                height as usize), data);
-
-            dataset.rasterband(index)?.write::<u16>((0, 0),
-                (width as usize, height as usize), &buffer)?;
-        },
-        GDALDataType::GDT_Float32 => {
-            // read rasterband
-            let mut data = Vec::new();
-            for _ in 0..size {
-                data.push(reader.read_f32::<BigEndian>()?);
-            }
-
-            let buffer = Buffer::new((width as usize,
-                height as usize), data);
-
-            dataset.rasterband(index)?.write::<f32>((0, 0),
-                (width as usize, height as usize), &buffer)?;
-        },
-        _ => unimplemented!(),
+        rasters.push(data);
     }
 
-    Ok(())
+    Ok(rasters)
 }
 
-pub fn write<T: Write>(dataset: &Dataset, writer: &mut T)
+pub fn write<B: Backend, T: Write>(dataset: &B, writer: &mut T)
+        -> Result<(), Box<dyn Error>> {
+    let raster = dataset.to_raster()?;
+    write_raster(&raster, writer)
+}
+
+pub fn write_raster<T: Write>(raster: &AnyRaster, writer: &mut T)
         -> Result<(), Box<dyn Error>> {
     // write image dimensions
-    let (width, height) = dataset.raster_size();
+    let (width, height) = raster.raster_size();
     writer.write_u32::<BigEndian>(width as u32)?;
     writer.write_u32::<BigEndian>(height as u32)?;
 
     // write geo transform
-    let transform = dataset.geo_transform()?;
+    let transform = <[f64; 6]>::from(raster.geo_transform());
     for val in transform.iter() {
         writer.write_f64::<BigEndian>(*val)?;
     }
 
     // write projection
-    let projection = dataset.projection();
+    let projection = raster.projection();
     writer.write_u32::<BigEndian>(projection.len() as u32)?;
     writer.write_all(projection.as_bytes())?;
 
-    // write gdal type and no_data value
-    let rasterband = dataset.rasterband(1)?;
-    writer.write_u32::<BigEndian>(rasterband.band_type())?;
-    match rasterband.no_data_value() {
+    // write data type and no_data value
+    writer.write_u32::<BigEndian>(raster.data_type().to_code())?;
+    match raster.no_data_value() {
         Some(value) => {
             writer.write_u8(1)?;
             writer.write_f64::<BigEndian>(value)?
@@ -144,47 +129,27 @@ pub fn write<T: Write>(dataset: &Dataset, writer: &mut T)
     }
 
     // write rasterbands
-    writer.write_u8(dataset.raster_count() as u8)?;
-    for i in 0..dataset.raster_count() {
-        write_raster(dataset, i+1, writer)?;
+    writer.write_u8(raster.raster_count() as u8)?;
+    match raster {
+        AnyRaster::Byte(raster) => write_rasters(raster, writer,
+            |x, pixel| x.write_u8(pixel)),
+        AnyRaster::Int16(raster) => write_rasters(raster, writer,
+            |x, pixel| x.write_i16::<BigEndian>(pixel)),
+        AnyRaster::UInt16(raster) => write_rasters(raster, writer,
+            |x, pixel| x.write_u16::<BigEndian>(pixel)),
+        AnyRaster::Float32(raster) => write_rasters(raster, writer,
+            |x, pixel| x.write_f32::<BigEndian>(pixel)),
     }
-
-    Ok(())
 }
 
-fn write_raster<T: Write>(dataset: &Dataset, index: isize,
-        writer: &mut T) -> Result<(), Box<dyn Error>> {
-    let gdal_type = dataset.rasterband(index)?.band_type();
-    writer.write_u32::<BigEndian>(gdal_type)?;
-
-    match gdal_type {
-        GDALDataType::GDT_Byte => {
-            let buffer = dataset.rasterband(index)?
-                .read_band_as::<u8>()?;
-            writer.write_all(&buffer.data)?;
-        },
-        GDALDataType::GDT_Int16 => {
-            let buffer = dataset.rasterband(index)?
-                .read_band_as::<i16>()?;
-            for pixel in buffer.data {
-                writer.write_i16::<BigEndian>(pixel)?;
-            }
-        },
-        GDALDataType::GDT_UInt16 => {
-            let buffer = dataset.rasterband(index)?
-                .read_band_as::<u16>()?;
-            for pixel in buffer.data {
-                writer.write_u16::<BigEndian>(pixel)?;
-            }
-        },
-        GDALDataType::GDT_Float32 => {
-            let buffer = dataset.rasterband(index)?
-                .read_band_as::<f32>()?;
-            for pixel in buffer.data {
-                writer.write_f32::<BigEndian>(pixel)?;
-            }
+fn write_rasters<T: Write, U: RasterType>(raster: &Raster<U>,
+        writer: &mut T, write_pixel: impl Fn(&mut T, U) -> std::io::Result<()>)
+        -> Result<(), Box<dyn Error>> {
+    for band in raster.bands() {
+        writer.write_u32::<BigEndian>(U::data_type().to_code())?;
+        for pixel in band.iter() {
+            write_pixel(writer, *pixel)?;
         }
-        _ => unimplemented!(),
     }
 
     Ok(())
@@ -192,11 +157,63 @@ fn write_raster<T: Write>(dataset: &Dataset, index: isize,
 
 #[cfg(test)]
 mod tests {
+    use crate::coordinate::GeoTransform;
+    use crate::raster::{AnyRaster, Raster};
+
+    #[cfg(feature = "gdal-backend")]
     use gdal::Dataset;
 
     use std::io::Cursor;
+    #[cfg(feature = "gdal-backend")]
     use std::path::Path;
 
+    #[test]
+    fn serialize_raster_cycle() {
+        let raster = AnyRaster::UInt16(Raster::new(2, 2,
+            vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]],
+            GeoTransform::new(0.0, 1.0, 0.0, 2.0, 0.0, -1.0),
+            "projection".to_string(),
+            Some(0.0)).expect("new raster"));
+
+        // write raster to buffer
+        let mut buffer = Vec::new();
+        super::write(&raster, &mut buffer).expect("write raster");
+
+        // read raster from buffer
+        let mut cursor = Cursor::new(buffer);
+        let raster2 = super::read_raster(&mut cursor)
+            .expect("read raster");
+        assert_eq!(raster, raster2);
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn serialize_invalid() {
+        use gdal::Driver;
+
+        // validate datasets without a geotransform fail
+        let driver = Driver::get("Mem").expect("get driver");
+        let dataset = driver.create_with_band_type::<u8>(
+            "", 2, 2, 1).expect("create dataset");
+
+        let mut buffer = Vec::new();
+        assert!(super::write(&dataset, &mut buffer).is_err());
+
+        // validate unsupported rasterband data types fail
+        let dataset = driver.create_with_band_type::<u8>(
+            "", 2, 2, 1).expect("create dataset");
+        dataset.set_geo_transform(&[0.0, 1.0, 0.0, 2.0, 0.0, -1.0])
+            .expect("set transform");
+        unsafe {
+            gdal_sys::GDALAddBand(dataset.c_dataset(),
+                gdal_sys::GDALDataType::GDT_Float64, std::ptr::null_mut());
+        }
+
+        let mut buffer = Vec::new();
+        assert!(super::write(&dataset, &mut buffer).is_err());
+    }
+
+    #[cfg(feature = "gdal-backend")]
     #[test]
     fn serialize_cycle() {
         // read dataset
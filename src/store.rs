@@ -0,0 +1,633 @@
+use gdal::{Dataset, Driver};
+
+use crate::coordinate::{Geocode, GeoTransform};
+use crate::sample::{Interpolation, Sample};
+use crate::transform::Tile;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const INDEX_FILENAME: &str = "index.tsv";
+
+pub type TileSeries = Vec<(TileEntry, Sample)>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TilePriority {
+    Coverage,
+    Recency,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileEntry {
+    pub coverage: f64,
+    pub geocode: String,
+    pub halo: (usize, usize, usize, usize),
+    pub platform: String,
+    pub timestamp: i64,
+    pub window: (f64, f64, f64, f64),
+}
+
+impl TileEntry {
+    pub fn key(&self) -> String {
+        format!("{}/{}/{}", self.platform, self.geocode, self.timestamp)
+    }
+
+    fn from_line(line: &str) -> Result<TileEntry, Box<dyn Error>> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 12 {
+            return Err(format!("invalid index entry '{}'", line).into());
+        }
+
+        Ok(TileEntry {
+            coverage: fields[3].parse::<f64>()?,
+            geocode: fields[1].to_string(),
+            halo: (fields[8].parse::<usize>()?, fields[9].parse::<usize>()?,
+                fields[10].parse::<usize>()?, fields[11].parse::<usize>()?),
+            platform: fields[0].to_string(),
+            timestamp: fields[2].parse::<i64>()?,
+            window: (fields[4].parse::<f64>()?, fields[5].parse::<f64>()?,
+                fields[6].parse::<f64>()?, fields[7].parse::<f64>()?),
+        })
+    }
+
+    fn to_line(&self) -> String {
+        format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.platform, self.geocode, self.timestamp, self.coverage,
+            self.window.0, self.window.1, self.window.2, self.window.3,
+            self.halo.0, self.halo.1, self.halo.2, self.halo.3)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TileQuery {
+    pub bounds: Option<(f64, f64, f64, f64)>,
+    pub geocode_prefix: Option<String>,
+    pub max_timestamp: Option<i64>,
+    pub min_coverage: Option<f64>,
+    pub min_timestamp: Option<i64>,
+    pub platform: Option<String>,
+}
+
+impl TileQuery {
+    pub fn matches(&self, entry: &TileEntry) -> bool {
+        if let Some((min_x, max_x, min_y, max_y)) = self.bounds {
+            let (window_min_x, window_max_x, window_min_y, window_max_y) =
+                entry.window;
+
+            // bounds crossing the antimeridian have minimum x > maximum x
+            let overlaps_x = match min_x > max_x {
+                true => window_max_x > min_x || window_min_x < max_x,
+                false => window_min_x < max_x && window_max_x > min_x,
+            };
+
+            if !overlaps_x || window_min_y >= max_y
+                    || window_max_y <= min_y {
+                return false;
+            }
+        }
+
+        if let Some(prefix) = &self.geocode_prefix {
+            if !entry.geocode.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(platform) = &self.platform {
+            if &entry.platform != platform {
+                return false;
+            }
+        }
+
+        if let Some(min_coverage) = self.min_coverage {
+            if entry.coverage < min_coverage {
+                return false;
+            }
+        }
+
+        // timestamp range is inclusive
+        if let Some(min_timestamp) = self.min_timestamp {
+            if entry.timestamp < min_timestamp {
+                return false;
+            }
+        }
+
+        if let Some(max_timestamp) = self.max_timestamp {
+            if entry.timestamp > max_timestamp {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+pub struct TileStore {
+    directory: PathBuf,
+    entries: Vec<TileEntry>,
+}
+
+impl TileStore {
+    pub fn open(directory: &Path) -> Result<TileStore, Box<dyn Error>> {
+        std::fs::create_dir_all(directory)?;
+
+        // read index entries if they exist
+        let mut entries = Vec::new();
+        let index_path = directory.join(INDEX_FILENAME);
+        if index_path.exists() {
+            let reader = BufReader::new(File::open(&index_path)?);
+            for line in reader.lines() {
+                let line = line?;
+                if !line.is_empty() {
+                    entries.push(TileEntry::from_line(&line)?);
+                }
+            }
+        }
+
+        Ok(TileStore { directory: directory.to_path_buf(), entries })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    pub fn entries(&self) -> &[TileEntry] {
+        &self.entries
+    }
+
+    pub fn insert(&mut self, platform: &str, geocode: &str,
+            timestamp: i64, tile: &Tile)
+            -> Result<TileEntry, Box<dyn Error>> {
+        // validate key components
+        for value in [platform, geocode].iter() {
+            if value.is_empty() || *value == ".."
+                    || value.contains(&['/', '\t', '\n'][..]) {
+                return Err(format!("invalid key component '{}'",
+                    value).into());
+            }
+        }
+
+        let entry = TileEntry {
+            coverage: tile.coverage,
+            geocode: geocode.to_string(),
+            halo: tile.halo,
+            platform: platform.to_string(),
+            timestamp,
+            window: tile.window,
+        };
+
+        // write tile dataset
+        let path = self.path(&entry);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let driver = Driver::get("GTiff")?;
+        tile.dataset.create_copy(&driver, &path.to_string_lossy())?;
+
+        // update index, rewriting if the key already exists
+        match self.entries.iter().position(|x| x.key() == entry.key()) {
+            Some(index) => {
+                self.entries[index] = entry.clone();
+                self.write_index()?;
+            },
+            None => {
+                let index_path = self.directory.join(INDEX_FILENAME);
+                let mut file = OpenOptions::new().create(true)
+                    .append(true).open(index_path)?;
+                writeln!(file, "{}", entry.to_line())?;
+                self.entries.push(entry.clone());
+            },
+        }
+
+        Ok(entry)
+    }
+
+    pub fn load(&self, entry: &TileEntry)
+            -> Result<Dataset, Box<dyn Error>> {
+        let dataset = Dataset::open(&self.path(entry))?;
+        Ok(dataset)
+    }
+
+    pub fn mosaic(&self, bounds: (f64, f64, f64, f64), epsg_code: u32,
+            geocode: Geocode, query: &TileQuery, priority: TilePriority)
+            -> Result<Option<Dataset>, Box<dyn Error>> {
+        // tile windows are stored in the geocode spatial reference system
+        let (min_x, max_x, min_y, max_y) = bounds;
+        let query = TileQuery {
+            bounds: Some(crate::coordinate::transform_bounds(min_x, max_x,
+                min_y, max_y, epsg_code, geocode.get_epsg_code())?),
+            ..query.clone()
+        };
+
+        // group tiles intersecting bounds by window
+        let mut windows: BTreeMap<&str, Vec<&TileEntry>> = BTreeMap::new();
+        for entry in self.query(&query) {
+            windows.entry(entry.geocode.as_str())
+                .or_default().push(entry);
+        }
+
+        let mut datasets = Vec::new();
+        for entries in windows.values_mut() {
+            // order tiles so the preferred tile is filled first
+            entries.sort_by(|a, b| compare_entries(a, b, priority));
+
+            // fill window with tiles sharing the preferred pixel grid
+            let mut fill_datasets = Vec::new();
+            for entry in entries.iter() {
+                // crop halos so neighboring windows do not overlap
+                let dataset = crop_halo(&self.load(entry)?, entry.halo)?;
+                if let Some(first) = fill_datasets.first() {
+                    if !same_grid(first, &dataset)? {
+                        continue;
+                    }
+                }
+
+                fill_datasets.push(dataset);
+            }
+
+            datasets.push(crate::fill(&fill_datasets)?);
+        }
+
+        if datasets.is_empty() {
+            return Ok(None);
+        }
+
+        // merge windows and clip to bounds
+        let merge_dataset = crate::transform::merge(&datasets)?;
+        crate::transform::split(&merge_dataset,
+            min_x, max_x, min_y, max_y, epsg_code)
+    }
+
+    pub fn path(&self, entry: &TileEntry) -> PathBuf {
+        self.directory.join(format!("{}.tif", entry.key()))
+    }
+
+    pub fn query(&self, query: &TileQuery) -> Vec<&TileEntry> {
+        self.entries.iter().filter(|x| query.matches(x)).collect()
+    }
+
+    pub fn sample(&self, points: &[(f64, f64)], epsg_code: u32,
+            geocode: Geocode, query: &TileQuery,
+            interpolation: Interpolation)
+            -> Result<Vec<TileSeries>, Box<dyn Error>> {
+        // tile windows are stored in the geocode spatial reference system
+        let coord_transform = crate::coordinate::get_coord_transform(
+            epsg_code, geocode.get_epsg_code())?;
+        let geocode_points: Vec<Option<(f64, f64)>> = points.iter()
+            .map(|(x, y)| crate::coordinate::transform_coord(*x, *y, 0.0,
+                &coord_transform).ok().map(|(x, y, _)| (x, y)))
+            .collect();
+
+        // sample each tile building a series for every point
+        let mut series: Vec<TileSeries> = vec![Vec::new(); points.len()];
+        for entry in self.query(query) {
+            // only sample points within the tile window
+            let (min_x, max_x, min_y, max_y) = entry.window;
+            let indices: Vec<usize> = geocode_points.iter().enumerate()
+                .filter(|(_, point)| match point {
+                    Some((x, y)) => *x >= min_x && *x <= max_x
+                        && *y >= min_y && *y <= max_y,
+                    None => false,
+                })
+                .map(|(i, _)| i).collect();
+
+            if indices.is_empty() {
+                continue;
+            }
+
+            let dataset = self.load(entry)?;
+            let tile_points: Vec<(f64, f64)> =
+                indices.iter().map(|i| points[*i]).collect();
+            let samples = crate::sample::sample(&dataset,
+                &tile_points, epsg_code, interpolation)?;
+
+            for (i, sample) in indices.into_iter().zip(samples) {
+                let sample = match sample {
+                    Some(sample) => sample,
+                    None => continue,
+                };
+
+                // overlapping tiles keep the most complete sample
+                match series[i].iter().position(|(x, _)|
+                        x.platform == entry.platform
+                            && x.timestamp == entry.timestamp) {
+                    Some(index) => {
+                        if sample.valid_count()
+                                > series[i][index].1.valid_count() {
+                            series[i][index] = (entry.clone(), sample);
+                        }
+                    },
+                    None => series[i].push((entry.clone(), sample)),
+                }
+            }
+        }
+
+        for point_series in series.iter_mut() {
+            point_series.sort_by(|a, b| a.0.timestamp.cmp(&b.0.timestamp)
+                .then_with(|| a.0.platform.cmp(&b.0.platform)));
+        }
+
+        Ok(series)
+    }
+
+    fn write_index(&self) -> Result<(), Box<dyn Error>> {
+        let file = File::create(self.directory.join(INDEX_FILENAME))?;
+        let mut writer = BufWriter::new(file);
+        for entry in self.entries.iter() {
+            writeln!(writer, "{}", entry.to_line())?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+fn compare_entries(a: &TileEntry, b: &TileEntry,
+        priority: TilePriority) -> Ordering {
+    let coverage = b.coverage.partial_cmp(&a.coverage)
+        .unwrap_or(Ordering::Equal);
+    let recency = b.timestamp.cmp(&a.timestamp);
+
+    match priority {
+        TilePriority::Coverage => coverage.then(recency),
+        TilePriority::Recency => recency.then(coverage),
+    }
+}
+
+fn crop_halo(dataset: &Dataset, halo: (usize, usize, usize, usize))
+        -> Result<Dataset, Box<dyn Error>> {
+    let (left, top, right, bottom) = halo;
+    let (width, height) = dataset.raster_size();
+    if left + right >= width || top + bottom >= height {
+        return Err("tile halo exceeds dimensions".into());
+    }
+
+    // initialize dataset over the core window
+    let (core_width, core_height) =
+        (width - left - right, height - top - bottom);
+    let rasterband = dataset.rasterband(1)?;
+    let driver = Driver::get("Mem")?;
+    let core_dataset = crate::init_dataset(&driver, "unreachable",
+        rasterband.band_type(), core_width as isize, core_height as isize,
+        dataset.raster_count(), rasterband.no_data_value())?;
+
+    let transform = GeoTransform::from(dataset.geo_transform()?)
+        .offset(left as f64, top as f64);
+    core_dataset.set_geo_transform(&<[f64; 6]>::from(transform))?;
+    core_dataset.set_projection(&dataset.projection())?;
+
+    // copy core window of each rasterband
+    for i in 0..dataset.raster_count() {
+        crate::copy_raster(dataset, i+1, (left as isize, top as isize),
+            (core_width, core_height), &core_dataset, i+1,
+            (0, 0), (core_width, core_height))?;
+    }
+
+    Ok(core_dataset)
+}
+
+fn same_grid(a: &Dataset, b: &Dataset) -> Result<bool, Box<dyn Error>> {
+    Ok(a.raster_size() == b.raster_size()
+        && a.raster_count() == b.raster_count()
+        && a.geo_transform()? == b.geo_transform()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TileEntry, TilePriority, TileQuery, TileStore};
+    use crate::coordinate::{Geocode, GeoTransform};
+    use crate::sample::Interpolation;
+    use crate::transform::{Halo, SplitOptions};
+
+    use gdal::Dataset;
+
+    use std::cmp::Ordering;
+    use std::path::{Path, PathBuf};
+
+    struct TempDirectory(PathBuf);
+
+    impl TempDirectory {
+        fn new(name: &str) -> TempDirectory {
+            // unique per process so concurrent test runs do not collide
+            let path = std::env::temp_dir().join(format!(
+                "st-image-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            TempDirectory(path)
+        }
+    }
+
+    impl Drop for TempDirectory {
+        fn drop(&mut self) {
+            // remove directory even if the test panics
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn store_mosaic() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // insert adjacent geohash tiles and a later duplicate with halos
+        let directory = TempDirectory::new("store-mosaic");
+        let mut store = TileStore::open(&directory.0).expect("open store");
+
+        for (x, timestamp, halo) in [(-105.9, 0, 4), (-104.6, 0, 4),
+                (-105.9, 10, 2)].iter() {
+            let options = SplitOptions {
+                halo: Some(Halo::Pixels(*halo)),
+                ..Default::default()
+            };
+
+            let code = Geocode::Geohash.get_code(*x, 44.25, 3)
+                .expect("geocode code");
+            let (min_x, max_x, min_y, max_y) = Geocode::Geohash
+                .get_window(&code).expect("geocode window");
+            let tile = crate::transform::split_tile(&dataset, min_x,
+                max_x, min_y, max_y, 4326, &options)
+                .expect("split tile").expect("tile");
+            assert_eq!(tile.halo, (*halo, *halo, *halo, *halo));
+
+            store.insert("modis", &code, *timestamp, &tile)
+                .expect("insert tile");
+        }
+
+        // mosaic bounds spanning both windows
+        let bounds = (-106.0, -104.5, 44.0, 44.5);
+        let mosaic = |bounds, epsg_code| store.mosaic(bounds, epsg_code,
+            Geocode::Geohash, &TileQuery::default(), TilePriority::Recency)
+            .expect("mosaic");
+
+        let mosaic_dataset = mosaic(bounds, 4326).expect("mosaic dataset");
+        assert_eq!(mosaic_dataset.raster_count(), dataset.raster_count());
+
+        // mosaic resolution matches the source dataset
+        let transform = GeoTransform::from(
+            dataset.geo_transform().expect("transform"));
+        let mosaic_transform = GeoTransform::from(
+            mosaic_dataset.geo_transform().expect("mosaic transform"));
+        assert_eq!(mosaic_transform.pixel_width(), transform.pixel_width());
+
+        // tiles cropped by their own halos share the window pixel grid
+        let window = store.entries()[0].window;
+        let cropped: Vec<Dataset> = store.entries().iter()
+            .filter(|x| x.window == window)
+            .map(|x| super::crop_halo(&store.load(x).expect("load tile"),
+                x.halo).expect("crop halo"))
+            .collect();
+        assert_eq!(cropped.len(), 2);
+        assert!(super::same_grid(&cropped[0], &cropped[1])
+            .expect("same grid"));
+
+        // bounds in other spatial reference systems match tile windows
+        let (min_x, max_x, min_y, max_y) = bounds;
+        let mercator_bounds = crate::coordinate::transform_bounds(min_x,
+            max_x, min_y, max_y, 4326, 3857).expect("transform bounds");
+        assert!(mosaic(mercator_bounds, 3857).is_some());
+
+        // bounds outside stored windows have no mosaic
+        assert!(mosaic((-80.0, -79.0, 44.0, 44.5), 4326).is_none());
+
+        // tiles with different resolutions are not merged
+        let tiles: Vec<Dataset> = store.entries().iter()
+            .map(|x| store.load(x).expect("load tile")).collect();
+        let resampled = crate::transform::resample_factor(&tiles[1],
+            0.5, crate::transform::Resampling::Nearest)
+            .expect("resample tile");
+        assert!(crate::transform::merge(&[tiles.into_iter().next()
+            .expect("tile"), resampled]).is_err());
+    }
+
+    #[test]
+    fn store_sample() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // insert adjacent geohash tiles and a later duplicate
+        let directory = TempDirectory::new("store-sample");
+        let mut store = TileStore::open(&directory.0).expect("open store");
+
+        let options = SplitOptions {
+            halo: Some(Halo::Pixels(4)),
+            ..Default::default()
+        };
+
+        for (x, timestamp) in [(-105.9, 0), (-104.6, 0), (-105.9, 10)].iter() {
+            let code = Geocode::Geohash.get_code(*x, 44.25, 3)
+                .expect("geocode code");
+            let (min_x, max_x, min_y, max_y) = Geocode::Geohash
+                .get_window(&code).expect("geocode window");
+            let tile = crate::transform::split_tile(&dataset, min_x,
+                max_x, min_y, max_y, 4326, &options)
+                .expect("split tile").expect("tile");
+
+            store.insert("modis", &code, *timestamp, &tile)
+                .expect("insert tile");
+        }
+
+        // points within each window, on the shared edge, and outside
+        let points = [(-105.9, 44.25), (-104.6, 44.25),
+            (-105.46875, 44.25), (-80.0, 44.25)];
+        let series = store.sample(&points, 4326, Geocode::Geohash,
+            &TileQuery::default(), Interpolation::Nearest)
+            .expect("sample store");
+
+        let timestamps: Vec<Vec<i64>> = series.iter()
+            .map(|x| x.iter().map(|(entry, _)| entry.timestamp).collect())
+            .collect();
+        assert_eq!(timestamps, vec![vec![0, 10], vec![0],
+            vec![0, 10], vec![]]);
+
+        // tile samples match sampling the source dataset
+        let samples = crate::sample::sample(&dataset, &points,
+            4326, Interpolation::Nearest).expect("sample dataset");
+        for (point_series, sample) in series.iter().zip(samples.iter()) {
+            for (_, tile_sample) in point_series.iter() {
+                assert_eq!(tile_sample.values,
+                    sample.as_ref().expect("sample").values);
+            }
+        }
+    }
+
+    #[test]
+    fn store_query() {
+        let entry = TileEntry {
+            coverage: 0.75,
+            geocode: "9xj".to_string(),
+            halo: (0, 0, 0, 0),
+            platform: "sentinel-2".to_string(),
+            timestamp: 1600000000,
+            window: (-105.46875, -104.0625, 39.375, 40.78125),
+        };
+
+        // validate index line round trip
+        let line = entry.to_line();
+        assert_eq!(TileEntry::from_line(&line).expect("parse entry"), entry);
+        assert_eq!(entry.key(), "sentinel-2/9xj/1600000000");
+
+        // validate query filters
+        assert!(TileQuery::default().matches(&entry));
+        assert!(TileQuery {
+            bounds: Some((-105.0, -100.0, 40.0, 45.0)),
+            geocode_prefix: Some("9x".to_string()),
+            min_timestamp: Some(1500000000),
+            max_timestamp: Some(1600000000),
+            ..Default::default()
+        }.matches(&entry));
+
+        assert!(!TileQuery { bounds: Some((-104.0, -100.0, 40.0, 45.0)),
+            ..Default::default() }.matches(&entry));
+        assert!(!TileQuery { geocode_prefix: Some("9y".to_string()),
+            ..Default::default() }.matches(&entry));
+        assert!(!TileQuery { min_coverage: Some(0.8),
+            ..Default::default() }.matches(&entry));
+        assert!(!TileQuery { platform: Some("landsat-8".to_string()),
+            ..Default::default() }.matches(&entry));
+        assert!(!TileQuery { max_timestamp: Some(1599999999),
+            ..Default::default() }.matches(&entry));
+
+        // bounds crossing the antimeridian match either side
+        let antimeridian_query = TileQuery {
+            bounds: Some((170.0, -170.0, 40.0, 45.0)),
+            ..Default::default()
+        };
+
+        for (window_min_x, window_max_x, matches) in [(175.0, 180.0, true),
+                (-180.0, -175.0, true), (-105.0, -100.0, false)].iter() {
+            let entry = TileEntry {
+                window: (*window_min_x, *window_max_x, 39.375, 40.78125),
+                ..entry.clone()
+            };
+
+            assert_eq!(antimeridian_query.matches(&entry), *matches);
+        }
+    }
+
+    #[test]
+    fn store_priority() {
+        let entry = |coverage, timestamp| TileEntry {
+            coverage,
+            geocode: "9xj".to_string(),
+            halo: (0, 0, 0, 0),
+            platform: "sentinel-2".to_string(),
+            timestamp,
+            window: (0.0, 1.0, 0.0, 1.0),
+        };
+
+        let (complete, recent) = (entry(1.0, 10), entry(0.5, 20));
+        assert_eq!(super::compare_entries(&complete, &recent,
+            TilePriority::Coverage), Ordering::Less);
+        assert_eq!(super::compare_entries(&complete, &recent,
+            TilePriority::Recency), Ordering::Greater);
+
+        // ties fall back to the secondary ordering
+        assert_eq!(super::compare_entries(&entry(1.0, 10), &entry(1.0, 20),
+            TilePriority::Coverage), Ordering::Greater);
+    }
+}
@@ -1,53 +1,105 @@
 use gdal::{Dataset, Driver};
-use gdal::spatial_ref::CoordTransform;
+use gdal::spatial_ref::{CoordTransform, SpatialRef};
+use gdal::raster::GdalType;
+use gdal_sys::{GDALDataType, GDALResampleAlg, GDALRIOResampleAlg};
 
+use crate::FromPrimitive;
+use crate::coordinate::{Geocode, GeoTransform};
+
+use std::collections::BTreeMap;
 use std::error::Error;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resampling {
+    Average,
+    Bilinear,
+    Cubic,
+    Mode,
+    Nearest,
+}
+
+impl Resampling {
+    pub fn to_rio_resample_alg(&self) -> GDALRIOResampleAlg::Type {
+        match self {
+            Resampling::Average => GDALRIOResampleAlg::GRIORA_Average,
+            Resampling::Bilinear => GDALRIOResampleAlg::GRIORA_Bilinear,
+            Resampling::Cubic => GDALRIOResampleAlg::GRIORA_Cubic,
+            Resampling::Mode => GDALRIOResampleAlg::GRIORA_Mode,
+            Resampling::Nearest =>
+                GDALRIOResampleAlg::GRIORA_NearestNeighbour,
+        }
+    }
+
+    pub fn to_resample_alg(&self) -> GDALResampleAlg::Type {
+        match self {
+            Resampling::Average => GDALResampleAlg::GRA_Average,
+            Resampling::Bilinear => GDALResampleAlg::GRA_Bilinear,
+            Resampling::Cubic => GDALResampleAlg::GRA_Cubic,
+            Resampling::Mode => GDALResampleAlg::GRA_Mode,
+            Resampling::Nearest => GDALResampleAlg::GRA_NearestNeighbour,
+        }
+    }
+}
+
 pub fn merge(datasets: &[Dataset])
         -> Result<Dataset, Box<dyn Error>> {
-    // TODO - ensure datasets are in same spatial reference system
+    let dataset = match datasets.first() {
+        Some(dataset) => dataset,
+        None => return Err("no datasets to merge".into()),
+    };
+
+    // compute bounds within the first datasets pixel space
+    let transform = GeoTransform::from(dataset.geo_transform()?);
+    let inverse = transform.invert()?;
+    let spatial_ref = SpatialRef::from_wkt(&dataset.projection())?;
 
-    // find minimum and maximum coordinates
-    let mut min_cx = f64::MAX;
-    let mut max_cx = f64::MIN;
-    let mut min_cy = f64::MAX;
-    let mut max_cy = f64::MIN;
+    let mut min_px = f64::MAX;
+    let mut max_px = f64::MIN;
+    let mut min_py = f64::MAX;
+    let mut max_py = f64::MIN;
 
     for dataset in datasets.iter() {
-        // TODO ensure transforms match
+        // ensure datasets share a spatial reference system and resolution
+        let image_spatial_ref =
+            SpatialRef::from_wkt(&dataset.projection())?;
+        let rv = unsafe {
+            gdal_sys::OSRIsSame(spatial_ref.to_c_hsrs(),
+                image_spatial_ref.to_c_hsrs())
+        };
+
+        if rv == 0 {
+            return Err("dataset spatial references do not match".into());
+        }
+
+        let image_transform = GeoTransform::from(dataset.geo_transform()?);
+        if !same_resolution(&transform, &image_transform) {
+            return Err("dataset resolutions do not match".into());
+        }
 
-        let transform = dataset.geo_transform()?;
         let (src_width, src_height) = dataset.raster_size();
         let (width, height) = (src_width as f64, src_height as f64);
 
-        let image_min_cx = transform[0];
-        let image_max_cx = transform[0] + (width * transform[1])
-            + (height * transform[2]);
-        let image_min_cy = transform[3] + (width * transform[4])
-            + (height * transform[5]);
-        let image_max_cy = transform[3];
-
-        min_cx = min_cx.min(image_min_cx);
-        max_cx = max_cx.max(image_max_cx);
-        min_cy = min_cy.min(image_min_cy);
-        max_cy = max_cy.max(image_max_cy);
-    }
+        // project all image corners to support rotated transforms
+        let corner_pixels = [(0.0, 0.0), (width, 0.0),
+            (0.0, height), (width, height)];
 
-    //println!("DST IMAGE BOUNDS {} {} {} {}",
-    //    min_cx, max_cx, min_cy, max_cy);
+        for (x, y) in corner_pixels.iter() {
+            let (cx, cy) = image_transform.pixel_to_world(*x, *y);
+            let (px, py) = inverse.pixel_to_world(cx, cy);
 
-    // compute merged image dimensions
-    let transform = datasets[0].geo_transform()?;
-    let min_px = (min_cx - transform[0]) / transform[1];
-    let max_px = (max_cx - transform[0]) / transform[1];
-    let min_py = (min_cy - transform[3]) / transform[5] * -1.0;
-    let max_py = (max_cy - transform[3]) / transform[5] * -1.0;
+            min_px = min_px.min(px);
+            max_px = max_px.max(px);
+            min_py = min_py.min(py);
+            max_py = max_py.max(py);
+        }
+    }
 
-    //println!("  PIXELS {} {} {} {}", min_px, max_px, min_py, max_py);
+    // snap to pixel grid to account for floating point error
+    let (min_px, max_px) = (min_px.round(), max_px.round());
+    let (min_py, max_py) = (min_py.round(), max_py.round());
 
     let dst_width = (max_px - min_px) as isize;
     let dst_height = (max_py - min_py) as isize;
-    //println!("DST IMAGE DIMENSIONS {} {}", dst_width, dst_height);
 
     // open memory driver
     let driver = Driver::get("Mem")?;
@@ -62,23 +114,20 @@ pub fn merge(datasets: &[Dataset])
         datasets[0].raster_count(), no_data_value)?;
 
     // modify transform
-    let mut merge_transform = datasets[0].geo_transform()?;
-    merge_transform[0] = min_cx;
-    merge_transform[3] = max_cy;
-
-    merge_dataset.set_geo_transform(&merge_transform)?;
+    let merge_transform = transform.offset(min_px, min_py);
+    merge_dataset.set_geo_transform(&<[f64; 6]>::from(merge_transform))?;
     merge_dataset.set_projection(&datasets[0].projection())?;
 
     // copy source rasters
     for dataset in datasets.iter() {
         // compute raster offsets
-        let transform = dataset.geo_transform()?;
+        let image_transform = GeoTransform::from(dataset.geo_transform()?);
         let (src_width, src_height) = dataset.raster_size();
 
-        let dst_x_offset = ((transform[0] - merge_transform[0])
-            / merge_transform[1]) as isize;
-        let dst_y_offset = ((transform[3] - merge_transform[3])
-            / merge_transform[5]) as isize;
+        let (origin_cx, origin_cy) = image_transform.origin();
+        let (px, py) = inverse.pixel_to_world(origin_cx, origin_cy);
+        let dst_x_offset = (px - min_px).round() as isize;
+        let dst_y_offset = (py - min_py).round() as isize;
 
         // copy all rasters
         for i in 0..dataset.raster_count() {
@@ -94,104 +143,503 @@ pub fn merge(datasets: &[Dataset])
     Ok(merge_dataset)
 }
 
+fn same_resolution(a: &GeoTransform, b: &GeoTransform) -> bool {
+    // compare pixel dimensions and rotations within a relative tolerance
+    let (a, b) = (<[f64; 6]>::from(*a), <[f64; 6]>::from(*b));
+    [1, 2, 4, 5].iter().all(|i| (a[*i] - b[*i]).abs()
+        <= a[*i].abs().max(b[*i].abs()) * 1e-9)
+}
+
+pub fn pyramid(dataset: &Dataset, geocode: Geocode,
+        min_precision: usize, max_precision: usize,
+        resampling: Resampling)
+        -> Result<BTreeMap<String, Dataset>, Box<dyn Error>> {
+    if min_precision == 0 || min_precision > max_precision {
+        return Err("invalid pyramid precision range".into());
+    }
+
+    // compute finest precision window boundaries for dataset
+    let epsg_code = geocode.get_epsg_code();
+    let (x_interval, y_interval) = geocode.get_intervals(max_precision);
+
+    let (image_min_cx, image_max_cx, image_min_cy, image_max_cy) =
+        crate::coordinate::get_bounds_densified(dataset, epsg_code,
+            crate::coordinate::DENSIFY_POINTS, true)?;
+
+    let window_bounds = crate::coordinate::get_windows(
+        image_min_cx, image_max_cx, image_min_cy, image_max_cy,
+            x_interval, y_interval);
+
+    // split dataset along finest precision boundaries
+    let mut tiles = BTreeMap::new();
+    for (min_cx, max_cx, min_cy, max_cy) in window_bounds {
+        let split_dataset = match split(dataset, min_cx,
+                max_cx, min_cy, max_cy, epsg_code)? {
+            Some(split_dataset) => split_dataset,
+            None => continue,
+        };
+
+        let code = geocode.get_code((min_cx + max_cx) / 2.0,
+            (min_cy + max_cy) / 2.0, max_precision)?;
+        tiles.insert(code, split_dataset);
+    }
+
+    // build coarser levels by merging and downsampling children
+    for precision in (min_precision..max_precision).rev() {
+        let (x_interval, y_interval) = geocode.get_intervals(precision);
+        let (child_x_interval, child_y_interval) =
+            geocode.get_intervals(precision + 1);
+
+        let x_factor = child_x_interval / x_interval;
+        let y_factor = child_y_interval / y_interval;
+
+        // group child codes by parent code
+        let mut parents: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for code in tiles.keys().filter(|x| x.len() == precision + 1) {
+            parents.entry(code[..precision].to_string())
+                .or_insert_with(Vec::new).push(code.clone());
+        }
+
+        for (parent_code, child_codes) in parents.iter() {
+            // merge child datasets
+            let children: Vec<Dataset> = child_codes.iter()
+                .filter_map(|x| tiles.remove(x)).collect();
+            let merge_dataset = merge(&children);
+
+            for (code, child) in child_codes.iter().zip(children) {
+                tiles.insert(code.clone(), child);
+            }
+
+            // downsample merged dataset to child resolution
+            let merge_dataset = merge_dataset?;
+            let (merge_width, merge_height) = merge_dataset.raster_size();
+            let width = ((merge_width as f64 * x_factor)
+                .round() as usize).max(1);
+            let height = ((merge_height as f64 * y_factor)
+                .round() as usize).max(1);
+
+            let parent_dataset = resample(&merge_dataset,
+                width, height, resampling)?;
+            tiles.insert(parent_code.clone(), parent_dataset);
+        }
+    }
+
+    Ok(tiles)
+}
+
+pub fn resample(dataset: &Dataset, width: usize, height: usize,
+        resampling: Resampling) -> Result<Dataset, Box<dyn Error>> {
+    let (src_width, src_height) = dataset.raster_size();
+    if width == 0 || height == 0 {
+        return Err("resample dimensions must be non-zero".into());
+    }
+
+    // open memory driver
+    let driver = Driver::get("Mem")?;
+
+    // initialize resample Dataset
+    let rasterband = dataset.rasterband(1)?;
+    let gdal_type = rasterband.band_type();
+    let no_data_value = rasterband.no_data_value();
+
+    let resample_dataset = crate::init_dataset(&driver,
+        "unreachable", gdal_type, width as isize, height as isize,
+        dataset.raster_count(), no_data_value)?;
+
+    // scale transform to the new pixel dimensions
+    let transform = GeoTransform::from(dataset.geo_transform()?).scale(
+        src_width as f64 / width as f64, src_height as f64 / height as f64);
+
+    resample_dataset.set_geo_transform(&<[f64; 6]>::from(transform))?;
+    resample_dataset.set_projection(&dataset.projection())?;
+
+    // copy resampled rasterband data to new image
+    for i in 0..dataset.raster_count() {
+        crate::resample_raster(dataset, i+1,
+            (0, 0),
+            (src_width, src_height),
+            &resample_dataset, i+1,
+            (0, 0),
+            (width, height),
+            resampling)?;
+    }
+
+    Ok(resample_dataset)
+}
+
+pub fn resample_factor(dataset: &Dataset, factor: f64,
+        resampling: Resampling) -> Result<Dataset, Box<dyn Error>> {
+    if factor <= 0.0 {
+        return Err("resample factor must be positive".into());
+    }
+
+    // compute target dimensions
+    let (src_width, src_height) = dataset.raster_size();
+    let width = ((src_width as f64 * factor).round() as usize).max(1);
+    let height = ((src_height as f64 * factor).round() as usize).max(1);
+
+    resample(dataset, width, height, resampling)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Halo {
+    Pixels(usize),
+    Units(f64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitOptions {
+    pub dimensions: Option<(usize, usize)>,
+    pub halo: Option<Halo>,
+    pub min_coverage: f64,
+    pub resampling: Resampling,
+}
+
+impl Default for SplitOptions {
+    fn default() -> SplitOptions {
+        SplitOptions {
+            dimensions: None,
+            halo: None,
+            min_coverage: 0.0,
+            resampling: Resampling::Nearest,
+        }
+    }
+}
+
+pub struct Tile {
+    pub coverage: f64,
+    pub dataset: Dataset,
+    pub halo: (usize, usize, usize, usize),
+    pub window: (f64, f64, f64, f64),
+}
+
+#[derive(Clone)]
+struct SplitWindow {
+    bounds: (isize, isize, isize, isize),
+    src_offset: (isize, isize),
+    buf_size: (usize, usize),
+    dst_offset: (isize, isize),
+    dst_size: (usize, usize),
+    transform: GeoTransform,
+    projection: String,
+}
+
 pub fn split(dataset: &Dataset, min_cx: f64, max_cx: f64,
         min_cy : f64, max_cy: f64, epsg_code: u32)
         -> Result<Option<Dataset>, Box<dyn Error>> {
-    let (src_width, src_height) = dataset.raster_size();
+    let tile = split_tile(dataset, min_cx, max_cx, min_cy,
+        max_cy, epsg_code, &SplitOptions::default())?;
 
-    // initialize CoordTransforms from dataset
-    let (mut transform, projection, src_spatial_ref, dst_spatial_ref) =
-        crate::coordinate::get_transform_refs(dataset, epsg_code)?;
-    let coord_transform = CoordTransform::new(
-        &src_spatial_ref, &dst_spatial_ref)?;
-    let reverse_transform = CoordTransform::new(
-        &dst_spatial_ref, &src_spatial_ref)?;
+    Ok(tile.map(|x| x.dataset))
+}
 
-    // compute center point pixels
-    let mid_cx = (min_cx + max_cx) / 2.0;
-    let mid_cy = (min_cy + max_cy) / 2.0;
+pub fn split_all(dataset: &Dataset, window_bounds: &[(f64, f64, f64, f64)],
+        epsg_code: u32, options: &SplitOptions)
+        -> Result<Vec<Tile>, Box<dyn Error>> {
+    let mut tiles = Vec::new();
+    for (min_cx, max_cx, min_cy, max_cy) in window_bounds.iter() {
+        if let Some(tile) = split_tile(dataset, *min_cx, *max_cx,
+                *min_cy, *max_cy, epsg_code, options)? {
+            tiles.push(tile);
+        }
+    }
 
-    let (center_tx, center_ty, _) = crate::coordinate::transform_coord(
-        mid_cx, mid_cy, 0.0, &reverse_transform)?;
+    Ok(tiles)
+}
 
-    let center_px = (center_tx - transform[0]) / transform[1];
-    let center_py = (center_ty - transform[3]) / transform[5];
+pub fn split_tile(dataset: &Dataset, min_cx: f64, max_cx: f64,
+        min_cy : f64, max_cy: f64, epsg_code: u32,
+        options: &SplitOptions) -> Result<Option<Tile>, Box<dyn Error>> {
+    if let Some(Halo::Units(units)) = options.halo {
+        if !units.is_finite() || units < 0.0 {
+            return Err(format!("invalid halo units '{}'", units).into());
+        }
+    }
 
-    // compute window pixel bounding box
-    let mut bound_min_px = center_px as isize;
-    let mut bound_max_px = center_px as isize;
-    let mut bound_min_py = center_py as isize;
-    let mut bound_max_py = center_py as isize;
-
-    let mut bound_min_cx;
-    let mut bound_max_cx;
-    let mut bound_min_cy;
-    let mut bound_max_cy;
-
-    loop {
-        // convert bounding pixels to coordinates
-        let pixels = vec![
-            (bound_min_px, bound_min_py, 0),
-            (bound_max_px, bound_min_py, 0),
-            (bound_min_px, bound_max_py, 0),
-            (bound_max_px, bound_max_py, 0)
-        ];
-
-        let (xs, ys, _) = crate::coordinate::transform_pixels(
-            &pixels, &transform, &coord_transform)?;
-
-        bound_min_cx = xs[0].max(xs[2]);
-        bound_max_cx = xs[1].min(xs[3]);
-        bound_min_cy = ys[2].max(ys[3]);
-        bound_max_cy = ys[0].min(ys[1]);
-
-        // check if bounding box envolopes window
-        if bound_min_cx <= min_cx
-                && bound_max_cx >= max_cx
-                && bound_min_cy <= min_cy
-                && bound_max_cy >= max_cy {
-                //&& &bound_max_cy >= min_cy { TODO - validate fix?
-            break;
+    // compute window pixel boundaries within dataset
+    let core_window = match get_split_window(dataset,
+            min_cx, max_cx, min_cy, max_cy, epsg_code, 0)? {
+        Some(core_window) => core_window,
+        None => return Ok(None),
+    };
+
+    // expand window boundaries by halo
+    let split_window = match options.halo {
+        Some(Halo::Pixels(pixels)) => get_split_window(dataset, min_cx,
+            max_cx, min_cy, max_cy, epsg_code, pixels as isize)?,
+        Some(Halo::Units(units)) => get_split_window(dataset,
+            min_cx - units, max_cx + units, min_cy - units,
+            max_cy + units, epsg_code, 0)?,
+        None => None,
+    }.unwrap_or_else(|| core_window.clone());
+
+    // split dataset if window coverage meets threshold
+    let window = (min_cx, max_cx, min_cy, max_cy);
+    match dataset.rasterband(1)?.band_type() {
+        GDALDataType::GDT_Byte => _split_tile::<u8>(dataset,
+            &split_window, &core_window, window, epsg_code, options),
+        GDALDataType::GDT_Int16 => _split_tile::<i16>(dataset,
+            &split_window, &core_window, window, epsg_code, options),
+        GDALDataType::GDT_UInt16 => _split_tile::<u16>(dataset,
+            &split_window, &core_window, window, epsg_code, options),
+        GDALDataType::GDT_Float32 => _split_tile::<f32>(dataset,
+            &split_window, &core_window, window, epsg_code, options),
+        band_type => Err(format!(
+            "unsupported band type '{}'", band_type).into()),
+    }
+}
+
+fn _split_tile<T: Copy + FromPrimitive + GdalType + PartialEq>(
+        dataset: &Dataset, split_window: &SplitWindow,
+        core_window: &SplitWindow, window: (f64, f64, f64, f64),
+        epsg_code: u32, options: &SplitOptions)
+        -> Result<Option<Tile>, Box<dyn Error>> {
+    // if dimensions are provided -> warp onto the exact window extent
+    if let Some((width, height)) = options.dimensions {
+        let (min_cx, max_cx, min_cy, max_cy) = window;
+        let max_cx = match min_cx > max_cx {
+            true => max_cx + 360.0,
+            false => max_cx,
+        };
+
+        let pixel_width = (max_cx - min_cx) / width as f64;
+        let pixel_height = (max_cy - min_cy) / height as f64;
+
+        // compute halo in destination pixels
+        let (halo_x, halo_y) = match options.halo {
+            Some(Halo::Pixels(pixels)) => (pixels, pixels),
+            Some(Halo::Units(units)) =>
+                ((units / pixel_width).round() as usize,
+                    (units / pixel_height).round() as usize),
+            None => (0, 0),
+        };
+
+        let transform = GeoTransform::new(
+            min_cx - (halo_x as f64 * pixel_width), pixel_width, 0.0,
+            max_cy + (halo_y as f64 * pixel_height), 0.0, -pixel_height);
+        let split_dataset = crate::warp::warp_window(dataset,
+            epsg_code, &transform, width + (halo_x * 2),
+            height + (halo_y * 2), options.resampling)?;
+
+        // compute coverage over the warped core window
+        let valid_count = get_valid_count::<T>(&split_dataset,
+            (halo_x as isize, halo_y as isize), (width, height))?;
+
+        let coverage = valid_count as f64 / (width * height) as f64;
+        if coverage < options.min_coverage {
+            return Ok(None);
         }
 
-        // increment one of the bounds
-        // TODO - need to fix this in the case where y transforms are non-negative
-        let bound_differences = vec![
-            bound_min_cx - min_cx,
-            max_cx - bound_max_cx, 
-            bound_min_cy - min_cy,
-            max_cy - bound_max_cy
-        ];
-
-        let (mut index, mut value) = (0, bound_differences[0]);
-        for (i, x) in bound_differences.iter().enumerate().skip(1) {
-            if x > &value {
-                value = *x;
-                index = i;
+        let halo = (halo_x, halo_y, halo_x, halo_y);
+        set_halo_metadata(&split_dataset, halo)?;
+
+        return Ok(Some(Tile {
+            coverage,
+            dataset: split_dataset,
+            halo,
+            window,
+        }));
+    }
+
+    // read rasterbands over the split window
+    let mut buffers = Vec::new();
+    let mut no_data_values = Vec::new();
+    for i in 0..dataset.raster_count() {
+        let rasterband = dataset.rasterband(i + 1)?;
+        buffers.push(rasterband.read_as::<T>(split_window.src_offset,
+            split_window.buf_size, split_window.buf_size)?);
+        no_data_values.push(rasterband.no_data_value());
+    }
+
+    // compute coverage over the core window from the rasterband buffers
+    let offset = (core_window.src_offset.0 - split_window.src_offset.0,
+        core_window.src_offset.1 - split_window.src_offset.1);
+    let data: Vec<&[T]> = buffers.iter().map(|x| &x.data[..]).collect();
+    let valid_count = count_valid(&data, &no_data_values,
+        split_window.buf_size.0, offset, core_window.buf_size);
+
+    let (core_width, core_height) = core_window.dst_size;
+    let coverage = valid_count as f64 / (core_width * core_height) as f64;
+    if coverage < options.min_coverage {
+        return Ok(None);
+    }
+
+    // compute halo pixels surrounding the core window
+    let (min_px, min_py, max_px, max_py) = split_window.bounds;
+    let (core_min_px, core_min_py, core_max_px, core_max_py) =
+        core_window.bounds;
+    let halo = ((core_min_px - min_px).max(0) as usize,
+        (core_min_py - min_py).max(0) as usize,
+        (max_px - core_max_px).max(0) as usize,
+        (max_py - core_max_py).max(0) as usize);
+
+    // open memory driver
+    let driver = Driver::get("Mem")?;
+
+    // initialize split Dataset
+    let rasterband = dataset.rasterband(1)?;
+    let no_data_value = rasterband.no_data_value();
+
+    let (dst_width, dst_height) = split_window.dst_size;
+    let split_dataset = crate::init_dataset(&driver, "unreachable",
+        T::gdal_type(), dst_width as isize, dst_height as isize,
+        dataset.raster_count(), no_data_value)?;
+
+    split_dataset.set_geo_transform(
+        &<[f64; 6]>::from(split_window.transform))?;
+    split_dataset.set_projection(&split_window.projection)?;
+    set_halo_metadata(&split_dataset, halo)?;
+
+    // copy rasterband data to new image
+    for (i, buffer) in buffers.iter().enumerate() {
+        let split_rasterband = split_dataset.rasterband(i as isize + 1)?;
+        split_rasterband.write::<T>(split_window.dst_offset,
+            split_window.buf_size, buffer)?;
+
+        // maintain rasterband metadata
+        if let Some(value) = no_data_values[i] {
+            split_rasterband.set_no_data_value(value)?;
+        }
+    }
+
+    Ok(Some(Tile {
+        coverage,
+        dataset: split_dataset,
+        halo,
+        window,
+    }))
+}
+
+fn get_valid_count<T: Copy + FromPrimitive + GdalType + PartialEq>(
+        dataset: &Dataset, offset: (isize, isize), size: (usize, usize))
+        -> Result<usize, Box<dyn Error>> {
+    let mut buffers = Vec::new();
+    let mut no_data_values = Vec::new();
+    for i in 0..dataset.raster_count() {
+        let rasterband = dataset.rasterband(i + 1)?;
+        buffers.push(rasterband.read_as::<T>(offset, size, size)?.data);
+        no_data_values.push(rasterband.no_data_value());
+    }
+
+    let data: Vec<&[T]> = buffers.iter().map(|x| &x[..]).collect();
+    Ok(count_valid(&data, &no_data_values, size.0, (0, 0), size))
+}
+
+fn count_valid<T: Copy + FromPrimitive + PartialEq>(buffers: &[&[T]],
+        no_data_values: &[Option<f64>], width: usize,
+        offset: (isize, isize), size: (usize, usize)) -> usize {
+    let no_data_values: Vec<T> = no_data_values.iter()
+        .map(|x| T::from_f64(x.unwrap_or(0.0))).collect();
+
+    // pixels are invalid only where every rasterband is no_data
+    let (x_offset, y_offset) = (offset.0.max(0) as usize,
+        offset.1.max(0) as usize);
+    let mut valid_count = 0;
+    for y in y_offset..y_offset + size.1 {
+        for x in x_offset..x_offset + size.0 {
+            let index = (y * width) + x;
+            if buffers.iter().zip(no_data_values.iter())
+                    .any(|(data, no_data_value)|
+                        data.get(index).map_or(false,
+                            |value| value != no_data_value)) {
+                valid_count += 1;
             }
         }
+    }
+
+    valid_count
+}
+
+fn set_halo_metadata(dataset: &Dataset,
+        halo: (usize, usize, usize, usize)) -> Result<(), Box<dyn Error>> {
+    // record halo so consumers may crop it
+    crate::set_metadata_item(dataset, "HALO_LEFT", &halo.0.to_string())?;
+    crate::set_metadata_item(dataset, "HALO_TOP", &halo.1.to_string())?;
+    crate::set_metadata_item(dataset, "HALO_RIGHT", &halo.2.to_string())?;
+    crate::set_metadata_item(dataset, "HALO_BOTTOM", &halo.3.to_string())
+}
+
+fn get_split_window(dataset: &Dataset, min_cx: f64, max_cx: f64,
+        min_cy : f64, max_cy: f64, epsg_code: u32, padding: isize)
+        -> Result<Option<SplitWindow>, Box<dyn Error>> {
+    let (src_width, src_height) = dataset.raster_size();
+
+    // initialize CoordTransform from dataset
+    let (transform, projection, src_spatial_ref, dst_spatial_ref) =
+        crate::coordinate::get_transform_refs(dataset, epsg_code)?;
+    let reverse_transform = CoordTransform::new(
+        &dst_spatial_ref, &src_spatial_ref)?;
+
+    // windows crossing the antimeridian have minimum x > maximum x
+    let max_cx = match min_cx > max_cx {
+        true => max_cx + 360.0,
+        false => max_cx,
+    };
+
+    // densify window boundary and convert to source pixel space
+    let (xs, ys) = crate::coordinate::densify_bounds(min_cx,
+        max_cx, min_cy, max_cy, crate::coordinate::DENSIFY_POINTS);
+    let (mut xs, ys) = match crate::coordinate::transform_valid_coords(
+            &xs, &ys, &reverse_transform) {
+        Ok(coords) => coords,
+        Err(_) => return Ok(None),
+    };
+
+    // unwrap geographic longitudes around the image center
+    if crate::coordinate::is_geographic(&src_spatial_ref)? {
+        let (center_cx, _) = transform.pixel_to_world(
+            src_width as f64 / 2.0, src_height as f64 / 2.0);
+        for x in xs.iter_mut().filter(|x| x.is_finite()) {
+            while *x - center_cx > 180.0 {
+                *x -= 360.0;
+            }
 
-        match index {
-            0 => bound_min_px -= 1,
-            1 => bound_max_px += 1,
-            2 => bound_max_py += 1,
-            3 => bound_min_py -= 1,
-            _ => unreachable!(),
+            while *x - center_cx < -180.0 {
+                *x += 360.0;
+            }
         }
     }
 
-    //println!("  PIXEL BOUNDS: {} {} {} {}", bound_min_px, 
-    //    bound_max_px, bound_min_py, bound_max_py);
+    let (pixel_xs, pixel_ys) =
+        crate::coordinate::invert_coords(&xs, &ys, &transform)?;
+
+    let mut pixel_min_x = f64::MAX;
+    let mut pixel_max_x = f64::MIN;
+    let mut pixel_min_y = f64::MAX;
+    let mut pixel_max_y = f64::MIN;
 
-    //println!("  COORDINATE BOUNDS: {} {} {} {}", bound_min_cx, 
-    //    bound_max_cx, bound_min_cy, bound_max_cy);
+    for (px, py) in pixel_xs.iter().zip(pixel_ys.iter()) {
+        if !px.is_finite() || !py.is_finite() {
+            continue;
+        }
 
-    //println!("  IMAGE DIMENSIONS: {} {}", 
-    //    bound_max_px - bound_min_px, bound_max_py - bound_min_py);
+        pixel_min_x = pixel_min_x.min(*px);
+        pixel_max_x = pixel_max_x.max(*px);
+        pixel_min_y = pixel_min_y.min(*py);
+        pixel_max_y = pixel_max_y.max(*py);
+    }
+
+    if pixel_min_x > pixel_max_x || pixel_min_y > pixel_max_y {
+        return Ok(None);
+    }
+
+    // compute window pixel bounding box
+    let bound_min_px = pixel_min_x.floor() as isize;
+    let bound_max_px =
+        (pixel_max_x.ceil() as isize).max(bound_min_px + 1);
+    let bound_min_py = pixel_min_y.floor() as isize;
+    let bound_max_py =
+        (pixel_max_y.ceil() as isize).max(bound_min_py + 1);
+
+    // pad bounding box
+    let (bound_min_px, bound_max_px) =
+        (bound_min_px - padding, bound_max_px + padding);
+    let (bound_min_py, bound_max_py) =
+        (bound_min_py - padding, bound_max_py + padding);
 
     // skip window if the pixel boundaries don't fall within image
-    if bound_max_px < 0 || bound_min_px >= src_width as isize
-            || bound_max_py < 0 || bound_min_py >= src_height as isize {
+    if bound_max_px <= 0 || bound_min_px >= src_width as isize
+            || bound_max_py <= 0 || bound_min_py >= src_height as isize {
         return Ok(None);
     }
 
@@ -207,58 +655,269 @@ pub fn split(dataset: &Dataset, min_cx: f64, max_cx: f64,
     let dst_x_offset = (0 - bound_min_px).max(0) as isize;
     let dst_y_offset = (0 - bound_min_py).max(0) as isize;
 
-    let dst_width = (bound_max_px - bound_min_px) as isize;
-    let dst_height = (bound_max_py - bound_min_py) as isize;
+    let dst_width = (bound_max_px - bound_min_px) as usize;
+    let dst_height = (bound_max_py - bound_min_py) as usize;
 
-    //println!("  SRC OFFSET: {} {}", src_x_offset, src_y_offset);
-    //println!("  SRC DIMENSIONS: {} {}", buf_width, buf_height);
-
-    //println!("  DST OFFSET: {} {}", dst_x_offset, dst_y_offset);
-    //println!("  DST DIMENSIONS: {} {}", dst_width, dst_height);
+    // modify transform
+    let split_transform =
+        transform.offset(bound_min_px as f64, bound_min_py as f64);
+
+    Ok(Some(SplitWindow {
+        bounds: (bound_min_px, bound_min_py, bound_max_px, bound_max_py),
+        src_offset: (src_x_offset, src_y_offset),
+        buf_size: (buf_width, buf_height),
+        dst_offset: (dst_x_offset, dst_y_offset),
+        dst_size: (dst_width, dst_height),
+        transform: split_transform,
+        projection,
+    }))
+}
 
-    // open memory driver
-    let driver = Driver::get("Mem")?;
+#[cfg(test)]
+mod tests {
+    use super::{Halo, Resampling, SplitOptions};
+    use crate::coordinate::Geocode;
 
-    // initialize split Dataset
-    let rasterband = dataset.rasterband(1)?;
-    let gdal_type = rasterband.band_type();
-    let no_data_value = rasterband.no_data_value();
+    use gdal::{Dataset, Driver};
+    use gdal::raster::Buffer;
 
-    let split_dataset = crate::init_dataset(&driver,
-        "unreachable", gdal_type, dst_width, dst_height,
-        dataset.raster_count(), no_data_value)?;
+    use std::path::Path;
 
-    // modify transform
-    //let mut transform = dataset.geo_transform()?;
-    transform[0] = transform[0] + (bound_min_px as f64 * transform[1])
-        + (bound_min_py as f64 * transform[2]);
-    transform[3] = transform[3] + (bound_min_px as f64 * transform[4])
-        + (bound_min_py as f64 * transform[5]);
+    #[test]
+    fn transform_resample() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // downsample dataset by half
+        let resample_dataset = super::resample_factor(&dataset,
+            0.5, Resampling::Average).expect("resample dataset");
+
+        // compare dimensions
+        let (width, height) = dataset.raster_size();
+        let (resample_width, resample_height) =
+            resample_dataset.raster_size();
+        assert_eq!(resample_width,
+            (width as f64 * 0.5).round() as usize);
+        assert_eq!(resample_height,
+            (height as f64 * 0.5).round() as usize);
+
+        // compare transforms
+        let transform = dataset.geo_transform().expect("transform");
+        let resample_transform = resample_dataset.geo_transform()
+            .expect("resample transform");
+        assert_eq!(transform[0], resample_transform[0]);
+        assert_eq!(transform[3], resample_transform[3]);
+
+        let x_scale = width as f64 / resample_width as f64;
+        assert!((transform[1] * x_scale
+            - resample_transform[1]).abs() < 0.0001);
+
+        assert_eq!(dataset.raster_count(),
+            resample_dataset.raster_count());
+    }
 
-    split_dataset.set_geo_transform(&transform)?;
-    split_dataset.set_projection(&projection)?;
+    #[test]
+    fn transform_pyramid() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        assert!(super::pyramid(&dataset, Geocode::Geohash,
+            0, 2, Resampling::Average).is_err());
+        assert!(super::pyramid(&dataset, Geocode::Geohash,
+            3, 2, Resampling::Average).is_err());
+
+        // build two geohash precision levels
+        let tiles = super::pyramid(&dataset, Geocode::Geohash,
+            1, 2, Resampling::Average).expect("build pyramid");
+        assert!(tiles.keys().all(|x| x.len() == 1 || x.len() == 2));
+
+        let children: Vec<&String> = tiles.keys()
+            .filter(|x| x.len() == 2).collect();
+        let mut parents: Vec<&str> = children.iter()
+            .map(|x| &x[..1]).collect();
+        parents.dedup();
+
+        // each parent is built from its children
+        assert!(!children.is_empty());
+        assert_eq!(tiles.len(), children.len() + parents.len());
+        for parent in parents.iter() {
+            let parent_dataset = &tiles[*parent];
+            assert_eq!(parent_dataset.raster_count(),
+                dataset.raster_count());
+        }
+    }
 
-    // copy rasterband data to new image
-    for i in 0..dataset.raster_count() {
-        crate::copy_raster(dataset, i+1, 
-            (src_x_offset, src_y_offset),
-            (buf_width, buf_height),
-            &split_dataset, i+1,
-            (dst_x_offset, dst_y_offset), 
-            (buf_width, buf_height))?;
+    #[test]
+    fn transform_split_coverage() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // window within the image
+        let tile = super::split_tile(&dataset, -106.0, -105.0, 44.0, 45.0,
+            4326, &SplitOptions::default()).expect("split tile")
+            .expect("tile within image");
+        assert!(tile.coverage > 0.0 && tile.coverage <= 1.0);
+        assert_eq!(tile.window, (-106.0, -105.0, 44.0, 45.0));
+
+        // window overlapping the western image edge
+        let edge_tile = super::split_tile(&dataset, -114.0, -112.0,
+            44.0, 45.0, 4326, &SplitOptions::default())
+            .expect("split tile").expect("tile overlapping image");
+        assert!(edge_tile.coverage > 0.0
+            && edge_tile.coverage < tile.coverage);
+
+        // tiles below the coverage threshold are skipped
+        let options = SplitOptions {
+            min_coverage: edge_tile.coverage + 0.01,
+            ..Default::default()
+        };
+
+        assert!(super::split_tile(&dataset, -114.0, -112.0, 44.0, 45.0,
+            4326, &options).expect("split tile").is_none());
+        assert!(super::split_tile(&dataset, -106.0, -105.0, 44.0, 45.0,
+            4326, &options).expect("split tile").is_some());
+
+        // pixels remain valid while any rasterband is valid
+        let driver = Driver::get("Mem").expect("get driver");
+        let mem_dataset = dataset.create_copy(&driver, "unreachable")
+            .expect("copy dataset");
+        let (width, height) = mem_dataset.raster_size();
+        let rasterband = mem_dataset.rasterband(1).expect("rasterband");
+        let no_data_value = rasterband.no_data_value().unwrap_or(0.0);
+        rasterband.write::<f32>((0, 0), (width, height),
+            &Buffer::new((width, height),
+                vec![no_data_value as f32; width * height]))
+            .expect("write rasterband");
+
+        let warp_options = SplitOptions {
+            dimensions: Some((64, 32)),
+            ..Default::default()
+        };
+
+        for options in [SplitOptions::default(), warp_options].iter() {
+            let tile = super::split_tile(&mem_dataset, -106.0, -105.0,
+                44.0, 45.0, 4326, options).expect("split tile")
+                .expect("tile within image");
+            assert!(tile.coverage > 0.0);
+        }
     }
 
-    Ok(Some(split_dataset))
-}
+    #[test]
+    fn transform_split_dimensions() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // warp onto the exact window extent
+        let options = SplitOptions {
+            dimensions: Some((64, 32)),
+            resampling: Resampling::Bilinear,
+            ..Default::default()
+        };
+
+        let tile = super::split_tile(&dataset, -106.0, -105.0, 44.0, 45.0,
+            4326, &options).expect("split tile").expect("tile");
+        assert_eq!(tile.dataset.raster_size(), (64, 32));
+        assert_eq!(tile.dataset.raster_count(), dataset.raster_count());
+
+        let transform = tile.dataset.geo_transform().expect("transform");
+        assert_eq!((transform[0], transform[3]), (-106.0, 45.0));
+        assert_eq!((transform[1], transform[5]), (1.0 / 64.0, -1.0 / 32.0));
+        assert!(tile.coverage > 0.0 && tile.coverage <= 1.0);
+
+        // coverage is evaluated on the warped window
+        let edge_tile = super::split_tile(&dataset, -114.0, -112.0,
+            44.0, 45.0, 4326, &options).expect("split tile")
+            .expect("tile overlapping image");
+        assert_eq!(edge_tile.dataset.raster_size(), (64, 32));
+        assert!(edge_tile.coverage > 0.0 && edge_tile.coverage < 0.5);
+
+        let options = SplitOptions {
+            min_coverage: 0.5,
+            ..options
+        };
+
+        assert!(super::split_tile(&dataset, -114.0, -112.0, 44.0, 45.0,
+            4326, &options).expect("split tile").is_none());
+    }
 
-#[cfg(test)]
-mod tests {
-    //use crate::coordinate::Geocode;
+    fn get_halo_metadata(dataset: &Dataset) -> Vec<usize> {
+        ["HALO_LEFT", "HALO_TOP", "HALO_RIGHT", "HALO_BOTTOM"].iter()
+            .map(|x| crate::get_metadata_item(dataset, x)
+                .expect("metadata item").expect("halo metadata")
+                .parse::<usize>().expect("parse halo"))
+            .collect()
+    }
 
-    //use gdal::{Dataset, Driver};
-    //use gdal_sys::GDALDataType;
+    #[test]
+    fn transform_split_halo() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        let split_tile = |options: &SplitOptions| super::split_tile(
+            &dataset, -106.0, -105.0, 44.0, 45.0, 4326, options);
+
+        let tile = split_tile(&SplitOptions::default())
+            .expect("split tile").expect("tile");
+        assert_eq!(tile.halo, (0, 0, 0, 0));
+        assert_eq!(get_halo_metadata(&tile.dataset), vec![0, 0, 0, 0]);
+        let (width, height) = tile.dataset.raster_size();
+
+        // pixel halos pad each side of the core window
+        let options = SplitOptions {
+            halo: Some(Halo::Pixels(3)),
+            ..Default::default()
+        };
+
+        let halo_tile = split_tile(&options)
+            .expect("split tile").expect("tile");
+        assert_eq!(halo_tile.halo, (3, 3, 3, 3));
+        assert_eq!(get_halo_metadata(&halo_tile.dataset), vec![3, 3, 3, 3]);
+        assert_eq!(halo_tile.dataset.raster_size(), (width + 6, height + 6));
+        assert_eq!(halo_tile.coverage, tile.coverage);
+
+        // unit halos expand the window in destination units
+        let options = SplitOptions {
+            halo: Some(Halo::Units(0.1)),
+            ..Default::default()
+        };
+
+        let halo_tile = split_tile(&options)
+            .expect("split tile").expect("tile");
+        let (left, top, right, bottom) = halo_tile.halo;
+        assert!(left > 0 && top > 0 && right > 0 && bottom > 0);
+        assert_eq!(get_halo_metadata(&halo_tile.dataset),
+            vec![left, top, right, bottom]);
+        assert_eq!(halo_tile.dataset.raster_size(),
+            (width + left + right, height + top + bottom));
+
+        // warped tiles record the halo in destination pixels
+        let options = SplitOptions {
+            dimensions: Some((64, 32)),
+            halo: Some(Halo::Units(1.0 / 32.0)),
+            ..Default::default()
+        };
+
+        let halo_tile = split_tile(&options)
+            .expect("split tile").expect("tile");
+        assert_eq!(halo_tile.halo, (2, 1, 2, 1));
+        assert_eq!(get_halo_metadata(&halo_tile.dataset), vec![2, 1, 2, 1]);
+        assert_eq!(halo_tile.dataset.raster_size(), (68, 34));
+
+        // invalid unit halos are rejected
+        for units in [-0.1, f64::NAN, f64::INFINITY].iter() {
+            let options = SplitOptions {
+                halo: Some(Halo::Units(*units)),
+                ..Default::default()
+            };
 
-    //use std::path::Path;
+            assert!(split_tile(&options).is_err());
+        }
+    }
 
     /*#[test]
     fn transform_merge() {
@@ -318,11 +977,6 @@ mod tests {
             //    &format!("examples/split/st-image-{}.tif", count))
             //    .expect("dataset copy");
 
-            // compute pixel coverage over split image
-            //let pixel_coverage = crate::coverage(&dataset)
-            //    .expect("dataset pixel coverage");
-            //println!("{} - {}", count, pixel_coverage);
-
             count += 1;
         }
     }*/
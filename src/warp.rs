@@ -0,0 +1,430 @@
+use gdal::{Dataset, Driver};
+use gdal::spatial_ref::SpatialRef;
+use gdal_sys::CPLErr;
+
+use crate::coordinate::GeoTransform;
+use crate::transform::Resampling;
+
+use std::error::Error;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+
+const NO_DATA_SENTINEL: f64 = -1.1e20;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GcpMethod {
+    Polynomial(usize),
+    ThinPlateSpline,
+}
+
+pub fn orthorectify(dataset: &Dataset, method: GcpMethod,
+        resolution: Option<f64>, resampling: Resampling)
+        -> Result<Dataset, Box<dyn Error>> {
+    // validate dataset has global control points (GCPs)
+    let gcp_count = unsafe {
+        gdal_sys::GDALGetGCPCount(dataset.c_dataset())
+    };
+
+    if gcp_count == 0 {
+        return Err("no GCPs found".into());
+    }
+
+    // polynomial orders require increasing numbers of GCPs
+    if let GcpMethod::Polynomial(order) = method {
+        let min_gcp_count = match order {
+            1 => 3,
+            2 => 6,
+            3 => 10,
+            _ => return Err(format!(
+                "unsupported polynomial order '{}'", order).into()),
+        };
+
+        if gcp_count < min_gcp_count {
+            return Err(format!("polynomial order {} requires at least \
+                {} GCPs", order, min_gcp_count).into());
+        }
+    }
+
+    // parse gcp projection
+    let rv = unsafe {
+        gdal_sys::GDALGetGCPProjection(dataset.c_dataset())
+    };
+
+    let c_str = unsafe { CStr::from_ptr(rv) };
+    let dst_projection = c_str.to_string_lossy().into_owned();
+
+    // initialize transformer options for gcp method
+    let mut options = vec![format!("DST_SRS={}", dst_projection)];
+    match method {
+        GcpMethod::Polynomial(order) => {
+            options.push("METHOD=GCP_POLYNOMIAL".to_string());
+            options.push(format!("MAX_GCP_ORDER={}", order));
+        },
+        GcpMethod::ThinPlateSpline =>
+            options.push("METHOD=GCP_TPS".to_string()),
+    }
+
+    warp(dataset, &options, &dst_projection, resolution, resampling)
+}
+
+pub fn reproject(dataset: &Dataset, epsg_code: u32,
+        resolution: Option<f64>, resampling: Resampling)
+        -> Result<Dataset, Box<dyn Error>> {
+    // initialize destination projection
+    let dst_spatial_ref = SpatialRef::from_epsg(epsg_code)?;
+    let dst_projection = dst_spatial_ref.to_wkt()?;
+
+    // GCP-only datasets are handled by the transformer
+    let options = vec![format!("DST_SRS={}", dst_projection)];
+    warp(dataset, &options, &dst_projection, resolution, resampling)
+}
+
+pub(crate) fn warp_window(dataset: &Dataset, epsg_code: u32,
+        transform: &GeoTransform, width: usize, height: usize,
+        resampling: Resampling) -> Result<Dataset, Box<dyn Error>> {
+    // initialize destination projection
+    let dst_spatial_ref = SpatialRef::from_epsg(epsg_code)?;
+    let dst_projection = dst_spatial_ref.to_wkt()?;
+
+    // warp dataset onto the provided pixel grid
+    let options = vec![format!("DST_SRS={}", dst_projection)];
+    let transformer = Transformer::new(dataset, &options)?;
+
+    warp_to(dataset, &transformer, &dst_projection,
+        transform, width, height, resampling)
+}
+
+fn warp(dataset: &Dataset, options: &[String], dst_projection: &str,
+        resolution: Option<f64>, resampling: Resampling)
+        -> Result<Dataset, Box<dyn Error>> {
+    // initialize transformer between source and destination
+    let transformer = Transformer::new(dataset, options)?;
+
+    // compute suggested destination transform and dimensions
+    let mut transform = [0.0f64; 6];
+    let (mut width, mut height): (c_int, c_int) = (0, 0);
+    let rv = unsafe {
+        gdal_sys::GDALSuggestedWarpOutput(dataset.c_dataset(),
+            Some(gdal_sys::GDALGenImgProjTransform),
+            transformer.c_transformer, transform.as_mut_ptr(),
+            &mut width, &mut height)
+    };
+
+    if rv != CPLErr::CE_None {
+        return Err("failed to compute warp output".into());
+    }
+
+    // override resolution if provided
+    let mut transform = GeoTransform::from(transform);
+    if let Some(resolution) = resolution {
+        let (min_cx, max_cy) = transform.origin();
+        let (max_cx, min_cy) = transform.pixel_to_world(
+            width as f64, height as f64);
+
+        width = ((max_cx - min_cx) / resolution).ceil() as c_int;
+        height = ((max_cy - min_cy) / resolution).ceil() as c_int;
+        transform = GeoTransform::new(min_cx, resolution, 0.0,
+            max_cy, 0.0, -resolution);
+    }
+
+    warp_to(dataset, &transformer, dst_projection, &transform,
+        width as usize, height as usize, resampling)
+}
+
+fn warp_to(dataset: &Dataset, transformer: &Transformer,
+        dst_projection: &str, transform: &GeoTransform, width: usize,
+        height: usize, resampling: Resampling)
+        -> Result<Dataset, Box<dyn Error>> {
+    if width == 0 || height == 0 {
+        return Err("warp output has no pixels".into());
+    }
+
+    // open memory driver
+    let driver = Driver::get("Mem")?;
+
+    // initialize warp Dataset
+    let gdal_type = dataset.rasterband(1)?.band_type();
+    let rasterband_count = dataset.raster_count();
+
+    let warp_dataset = crate::init_dataset(&driver, "unreachable",
+        gdal_type, width as isize, height as isize,
+        rasterband_count, None)?;
+
+    // maintain each rasterband's own no_data value
+    let mut no_data_values = Vec::new();
+    for i in 0..rasterband_count {
+        let no_data_value = dataset.rasterband(i + 1)?.no_data_value();
+        if let Some(value) = no_data_value {
+            warp_dataset.rasterband(i + 1)?.set_no_data_value(value)?;
+        }
+
+        no_data_values.push(no_data_value);
+    }
+
+    let mut dst_transform: [f64; 6] = (*transform).into();
+    warp_dataset.set_geo_transform(&dst_transform)?;
+    warp_dataset.set_projection(dst_projection)?;
+
+    // point transformer at destination transform
+    unsafe {
+        gdal_sys::GDALSetGenImgProjTransformerDstGeoTransform(
+            transformer.c_transformer, dst_transform.as_mut_ptr());
+    }
+
+    // initialize warp options
+    let init_dest = CString::new("INIT_DEST=NO_DATA")?;
+    unsafe {
+        let warp_options = gdal_sys::GDALCreateWarpOptions();
+        (*warp_options).hSrcDS = dataset.c_dataset();
+        (*warp_options).hDstDS = warp_dataset.c_dataset();
+        (*warp_options).eResampleAlg = resampling.to_resample_alg();
+        (*warp_options).pfnTransformer =
+            Some(gdal_sys::GDALGenImgProjTransform);
+        (*warp_options).pTransformerArg = transformer.c_transformer;
+        (*warp_options).papszWarpOptions = gdal_sys::CSLAddString(
+            (*warp_options).papszWarpOptions, init_dest.as_ptr());
+
+        // map all rasterbands
+        let band_count = rasterband_count as usize;
+        let band_size = std::mem::size_of::<c_int>() * band_count;
+        (*warp_options).nBandCount = band_count as c_int;
+        (*warp_options).panSrcBands =
+            gdal_sys::CPLMalloc(band_size) as *mut c_int;
+        (*warp_options).panDstBands =
+            gdal_sys::CPLMalloc(band_size) as *mut c_int;
+
+        for i in 0..band_count {
+            *(*warp_options).panSrcBands.add(i) = (i + 1) as c_int;
+            *(*warp_options).panDstBands.add(i) = (i + 1) as c_int;
+        }
+
+        // maintain no_data values across warp
+        if no_data_values.iter().any(|x| x.is_some()) {
+            let no_data_size = std::mem::size_of::<f64>() * band_count;
+            (*warp_options).padfSrcNoDataReal =
+                gdal_sys::CPLMalloc(no_data_size) as *mut f64;
+            (*warp_options).padfDstNoDataReal =
+                gdal_sys::CPLMalloc(no_data_size) as *mut f64;
+
+            // bands without a no_data value use a sentinel that
+            //   matches no source pixel, as gdalwarp does
+            for (i, no_data_value) in no_data_values.iter().enumerate() {
+                *(*warp_options).padfSrcNoDataReal.add(i) =
+                    no_data_value.unwrap_or(NO_DATA_SENTINEL);
+                *(*warp_options).padfDstNoDataReal.add(i) =
+                    no_data_value.unwrap_or(0.0);
+            }
+        }
+
+        // execute warp
+        let operation = gdal_sys::GDALCreateWarpOperation(warp_options);
+        let rv = match operation.is_null() {
+            true => CPLErr::CE_Failure,
+            false => gdal_sys::GDALChunkAndWarpImage(operation, 0, 0,
+                width as c_int, height as c_int),
+        };
+
+        // the transformer is owned by Transformer
+        (*warp_options).pTransformerArg = std::ptr::null_mut();
+        if !operation.is_null() {
+            gdal_sys::GDALDestroyWarpOperation(operation);
+        }
+        gdal_sys::GDALDestroyWarpOptions(warp_options);
+
+        if rv != CPLErr::CE_None {
+            return Err("failed to warp dataset".into());
+        }
+    }
+
+    Ok(warp_dataset)
+}
+
+struct Transformer {
+    c_transformer: *mut c_void,
+}
+
+impl Transformer {
+    fn new(dataset: &Dataset, options: &[String])
+            -> Result<Transformer, Box<dyn Error>> {
+        // convert options to null terminated string list
+        let c_options = options.iter()
+            .map(|x| CString::new(x.as_str()))
+            .collect::<Result<Vec<CString>, _>>()?;
+        let mut c_option_ptrs: Vec<*mut c_char> = c_options.iter()
+            .map(|x| x.as_ptr() as *mut c_char).collect();
+        c_option_ptrs.push(std::ptr::null_mut());
+
+        let c_transformer = unsafe {
+            gdal_sys::GDALCreateGenImgProjTransformer2(
+                dataset.c_dataset(), std::ptr::null_mut(),
+                c_option_ptrs.as_mut_ptr())
+        };
+
+        if c_transformer.is_null() {
+            return Err("failed to initialize transformer".into());
+        }
+
+        Ok(Transformer { c_transformer })
+    }
+}
+
+impl Drop for Transformer {
+    fn drop(&mut self) {
+        unsafe {
+            gdal_sys::GDALDestroyGenImgProjTransformer(self.c_transformer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GcpMethod;
+    use crate::transform::Resampling;
+
+    use gdal::{Dataset, Driver};
+    use gdal_sys::{GDALDataType, GDAL_GCP};
+
+    use std::ffi::CString;
+    use std::os::raw::c_int;
+    use std::path::Path;
+
+    fn gcp_dataset(grid_size: usize) -> Dataset {
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+        let transform = dataset.geo_transform().expect("transform");
+        let (width, height) = dataset.raster_size();
+
+        // copy the first rasterband without a transform
+        let driver = Driver::get("Mem").expect("get driver");
+        let gcp_dataset = crate::init_dataset(&driver, "unreachable",
+            dataset.rasterband(1).expect("rasterband").band_type(),
+            width as isize, height as isize, 1, None)
+            .expect("init dataset");
+        crate::copy_raster(&dataset, 1, (0, 0), (width, height),
+            &gcp_dataset, 1, (0, 0), (width, height)).expect("copy raster");
+
+        // georeference a grid of pixels with GCPs
+        let c_id = CString::new("").expect("gcp id");
+        let mut gcps = Vec::new();
+        for i in 0..grid_size {
+            for j in 0..grid_size {
+                let x = (width * i) as f64 / (grid_size - 1) as f64;
+                let y = (height * j) as f64 / (grid_size - 1) as f64;
+
+                gcps.push(GDAL_GCP {
+                    pszId: c_id.as_ptr() as *mut _,
+                    pszInfo: c_id.as_ptr() as *mut _,
+                    dfGCPPixel: x,
+                    dfGCPLine: y,
+                    dfGCPX: transform[0] + (x * transform[1]),
+                    dfGCPY: transform[3] + (y * transform[5]),
+                    dfGCPZ: 0.0,
+                });
+            }
+        }
+
+        let c_projection = CString::new(dataset.projection())
+            .expect("gcp projection");
+        unsafe {
+            gdal_sys::GDALSetGCPs(gcp_dataset.c_dataset(),
+                gcps.len() as c_int, gcps.as_ptr(), c_projection.as_ptr());
+        }
+
+        gcp_dataset
+    }
+
+    #[test]
+    fn warp_orthorectify() {
+        // affine GCPs reproduce the original pixel grid
+        let dataset = gcp_dataset(2);
+        let orthorectify_dataset = super::orthorectify(&dataset,
+            GcpMethod::Polynomial(1), None, Resampling::Nearest)
+            .expect("orthorectify dataset");
+
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let transform = Dataset::open(path).expect("open dataset")
+            .geo_transform().expect("transform");
+        let orthorectify_transform = orthorectify_dataset.geo_transform()
+            .expect("orthorectify transform");
+        assert!((transform[0] - orthorectify_transform[0]).abs()
+            < transform[1]);
+        assert!((transform[3] - orthorectify_transform[3]).abs()
+            < transform[1]);
+
+        // validate polynomial orders and required GCP counts
+        for order in [0, 2, 3, 4].iter() {
+            assert!(super::orthorectify(&dataset,
+                GcpMethod::Polynomial(*order), None,
+                Resampling::Nearest).is_err());
+        }
+
+        let dataset = gcp_dataset(4);
+        assert!(super::orthorectify(&dataset, GcpMethod::Polynomial(3),
+            None, Resampling::Nearest).is_ok());
+
+        // thin plate spline interpolates the same GCP grid
+        let tps_dataset = super::orthorectify(&dataset,
+            GcpMethod::ThinPlateSpline, None, Resampling::Nearest)
+            .expect("orthorectify tps dataset");
+        let tps_transform = tps_dataset.geo_transform()
+            .expect("tps transform");
+        assert!((transform[0] - tps_transform[0]).abs() < transform[1]);
+        assert!((transform[3] - tps_transform[3]).abs() < transform[1]);
+        assert_eq!(tps_dataset.raster_count(), dataset.raster_count());
+    }
+
+    #[test]
+    fn warp_orthorectify_gcp_count() {
+        // datasets without GCPs can not be orthorectified
+        let driver = Driver::get("Mem").expect("get driver");
+        let dataset = crate::init_dataset(&driver, "unreachable",
+            GDALDataType::GDT_Byte, 4, 4, 1, None)
+            .expect("init dataset");
+        let error = super::orthorectify(&dataset, GcpMethod::Polynomial(1),
+            None, Resampling::Nearest).err().expect("no GCPs");
+        assert_eq!(error.to_string(), "no GCPs found");
+
+        // four GCPs are too few for second order polynomials
+        let dataset = gcp_dataset(2);
+        let error = super::orthorectify(&dataset, GcpMethod::Polynomial(2),
+            None, Resampling::Nearest).err().expect("too few GCPs");
+        assert_eq!(error.to_string(),
+            "polynomial order 2 requires at least 6 GCPs");
+    }
+
+    #[test]
+    fn warp_reproject() {
+        // read dataset
+        let path = Path::new("fixtures/MCD43A4.h10v04.006.tif");
+        let dataset = Dataset::open(path).expect("open dataset");
+
+        // reproject sinusoidal dataset to wgs84
+        let reproject_dataset = super::reproject(&dataset, 4326,
+            Some(0.01), Resampling::Nearest).expect("reproject dataset");
+
+        // compare transform resolution
+        let transform = reproject_dataset.geo_transform()
+            .expect("reproject transform");
+        assert_eq!(transform[1], 0.01);
+        assert_eq!(transform[5], -0.01);
+
+        assert_eq!(dataset.raster_count(),
+            reproject_dataset.raster_count());
+
+        // rasterbands keep their own no_data values
+        let driver = Driver::get("Mem").expect("get driver");
+        let mem_dataset = dataset.create_copy(&driver, "unreachable")
+            .expect("copy dataset");
+        mem_dataset.rasterband(2).expect("rasterband")
+            .set_no_data_value(0.0).expect("set no_data value");
+
+        let reproject_dataset = super::reproject(&mem_dataset, 4326,
+            Some(0.01), Resampling::Nearest).expect("reproject dataset");
+        for i in 1..=dataset.raster_count() {
+            assert_eq!(reproject_dataset.rasterband(i)
+                .expect("rasterband").no_data_value(),
+                mem_dataset.rasterband(i)
+                .expect("rasterband").no_data_value());
+        }
+    }
+}
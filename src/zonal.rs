@@ -0,0 +1,317 @@
+#[cfg(feature = "gdal-backend")]
+use gdal::Dataset;
+#[cfg(feature = "gdal-backend")]
+use gdal::spatial_ref::CoordTransform;
+
+use crate::backend::Backend;
+use crate::geometry::Geometry;
+use crate::raster::{AnyRaster, Raster, RasterType};
+
+use std::collections::BTreeMap;
+use std::error::Error;
+#[cfg(feature = "gdal-backend")]
+use std::ffi::{CStr, CString};
+
+#[cfg(feature = "gdal-backend")]
+const GDAL_OF_VECTOR: std::os::raw::c_uint = 0x04;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zone {
+    pub geometry: Geometry,
+    pub id: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZoneStatistics {
+    pub count: usize,
+    pub mean: Option<f64>,
+    pub percentiles: Vec<f64>,
+    pub sum: f64,
+}
+
+impl ZoneStatistics {
+    fn from_values(values: &mut [f64], percentiles: &[f64])
+            -> ZoneStatistics {
+        values.sort_by(|a, b| a.partial_cmp(b)
+            .unwrap_or(std::cmp::Ordering::Equal));
+
+        // zones without valid pixels have no mean or percentiles
+        let sum = values.iter().sum::<f64>();
+        let (mean, percentiles) = match values.is_empty() {
+            true => (None, Vec::new()),
+            false => (Some(sum / values.len() as f64), percentiles.iter()
+                .map(|x| crate::percentile(values, *x)).collect()),
+        };
+
+        ZoneStatistics { count: values.len(), mean, percentiles, sum }
+    }
+}
+
+pub fn get_zonal_statistics<B: Backend>(dataset: &B, zones: &[Zone],
+        percentiles: &[f64])
+        -> Result<BTreeMap<String, Vec<ZoneStatistics>>, Box<dyn Error>> {
+    let raster = dataset.to_raster()?;
+    let no_data_values = dataset.no_data_values()?;
+    match raster.as_ref() {
+        AnyRaster::Byte(raster) => _get_zonal_statistics(raster,
+            &no_data_values, zones, percentiles),
+        AnyRaster::Int16(raster) => _get_zonal_statistics(raster,
+            &no_data_values, zones, percentiles),
+        AnyRaster::UInt16(raster) => _get_zonal_statistics(raster,
+            &no_data_values, zones, percentiles),
+        AnyRaster::Float32(raster) => _get_zonal_statistics(raster,
+            &no_data_values, zones, percentiles),
+    }
+}
+
+fn _get_zonal_statistics<T: RasterType>(raster: &Raster<T>,
+        no_data_values: &[Option<f64>], zones: &[Zone], percentiles: &[f64])
+        -> Result<BTreeMap<String, Vec<ZoneStatistics>>, Box<dyn Error>> {
+    let (width, height) = raster.raster_size();
+    let no_data_values: Vec<Option<T>> = no_data_values.iter()
+        .map(|x| x.map(T::from_f64)).collect();
+
+    let mut statistics = BTreeMap::new();
+    for zone in zones.iter() {
+        if statistics.contains_key(&zone.id) {
+            return Err(format!("duplicate zone id '{}'", zone.id).into());
+        }
+
+        // rasterize zone over its pixel bounding box
+        let pixel_geometry = zone.geometry
+            .to_pixels(&raster.geo_transform())?;
+        let (min_x, max_x, min_y, max_y) = pixel_geometry.bounds();
+
+        let min_px = (min_x.floor() as isize).clamp(0, width as isize);
+        let max_px = (max_x.ceil() as isize).clamp(min_px, width as isize);
+        let min_py = (min_y.floor() as isize).clamp(0, height as isize);
+        let max_py = (max_y.ceil() as isize)
+            .clamp(min_py, height as isize);
+
+        let (mask_width, mask_height) =
+            ((max_px - min_px) as usize, (max_py - min_py) as usize);
+        let mask = pixel_geometry.get_mask((min_px, min_py),
+            (mask_width, mask_height));
+
+        // collect valid pixels within zone for each band
+        let mut zone_statistics = Vec::new();
+        for (band, no_data_value) in raster.bands().iter()
+                .zip(no_data_values.iter()) {
+            let mut values = Vec::new();
+            for (i, inside) in mask.iter().enumerate() {
+                let x = min_px as usize + (i % mask_width);
+                let y = min_py as usize + (i / mask_width);
+
+                let pixel = band[(y * width) + x];
+                if *inside && Some(pixel) != *no_data_value {
+                    values.push(pixel.to_f64());
+                }
+            }
+
+            zone_statistics.push(
+                ZoneStatistics::from_values(&mut values, percentiles));
+        }
+
+        statistics.insert(zone.id.clone(), zone_statistics);
+    }
+
+    Ok(statistics)
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn get_zonal_statistics_epsg(dataset: &Dataset, zones: &[Zone],
+        epsg_code: u32, percentiles: &[f64])
+        -> Result<BTreeMap<String, Vec<ZoneStatistics>>, Box<dyn Error>> {
+    // transform zones into the dataset coordinate system
+    let (_, _, src_spatial_ref, dst_spatial_ref) =
+        crate::coordinate::get_transform_refs(dataset, epsg_code)?;
+    let reverse_transform = CoordTransform::new(
+        &dst_spatial_ref, &src_spatial_ref)?;
+
+    let mut transformed_zones = Vec::new();
+    for zone in zones.iter() {
+        transformed_zones.push(Zone {
+            geometry: zone.geometry.transform(&reverse_transform)?,
+            id: zone.id.clone(),
+        });
+    }
+
+    get_zonal_statistics(dataset, &transformed_zones, percentiles)
+}
+
+#[cfg(feature = "gdal-backend")]
+pub fn read_zones(source: &str, layer: Option<&str>,
+        id_field: Option<&str>) -> Result<Vec<Zone>, Box<dyn Error>> {
+    // open geojson text or a vector file through ogr
+    let c_source = CString::new(source)?;
+    let c_dataset = unsafe {
+        gdal_sys::GDALOpenEx(c_source.as_ptr(), GDAL_OF_VECTOR,
+            std::ptr::null(), std::ptr::null(), std::ptr::null())
+    };
+
+    if c_dataset.is_null() {
+        return Err(format!("failed to open zones '{}'", source).into());
+    }
+
+    let result = unsafe { _read_zones(c_dataset, layer, id_field) };
+    unsafe { gdal_sys::GDALClose(c_dataset) };
+    result
+}
+
+#[cfg(feature = "gdal-backend")]
+unsafe fn _read_zones(c_dataset: gdal_sys::GDALDatasetH,
+        layer: Option<&str>, id_field: Option<&str>)
+        -> Result<Vec<Zone>, Box<dyn Error>> {
+    // identify layer by name or default to the first layer
+    let c_layer = match layer {
+        Some(layer) => {
+            let c_name = CString::new(layer)?;
+            gdal_sys::GDALDatasetGetLayerByName(c_dataset, c_name.as_ptr())
+        },
+        None => gdal_sys::GDALDatasetGetLayer(c_dataset, 0),
+    };
+
+    if c_layer.is_null() {
+        return Err("zone layer not found".into());
+    }
+
+    let c_field = match id_field {
+        Some(id_field) => Some(CString::new(id_field)?),
+        None => None,
+    };
+
+    let mut zones = Vec::new();
+    gdal_sys::OGR_L_ResetReading(c_layer);
+    loop {
+        let c_feature = gdal_sys::OGR_L_GetNextFeature(c_layer);
+        if c_feature.is_null() {
+            break;
+        }
+
+        // identify zone by attribute or feature id
+        let id = match &c_field {
+            Some(c_field) => {
+                let index = gdal_sys::OGR_F_GetFieldIndex(c_feature,
+                    c_field.as_ptr());
+                if index < 0 {
+                    gdal_sys::OGR_F_Destroy(c_feature);
+                    return Err(format!("zone field '{}' not found",
+                        c_field.to_string_lossy()).into());
+                }
+
+                let c_value =
+                    gdal_sys::OGR_F_GetFieldAsString(c_feature, index);
+                CStr::from_ptr(c_value).to_string_lossy().into_owned()
+            },
+            None => gdal_sys::OGR_F_GetFID(c_feature).to_string(),
+        };
+
+        // skip features without geometries
+        let c_geometry = gdal_sys::OGR_F_GetGeometryRef(c_feature);
+        let geometry = match c_geometry.is_null() {
+            true => None,
+            false => Some(Geometry::from_c_geometry(c_geometry)),
+        };
+
+        gdal_sys::OGR_F_Destroy(c_feature);
+        if let Some(geometry) = geometry {
+            if zones.iter().any(|x: &Zone| x.id == id) {
+                return Err(format!("duplicate zone id '{}'", id).into());
+            }
+
+            zones.push(Zone { geometry: geometry?, id });
+        }
+    }
+
+    Ok(zones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Zone, ZoneStatistics};
+    use crate::coordinate::GeoTransform;
+    use crate::geometry::Geometry;
+    use crate::raster::{AnyRaster, Raster};
+
+    #[test]
+    fn zonal_statistics() {
+        let raster = AnyRaster::UInt16(Raster::new(4, 2,
+            vec![vec![1, 2, 3, 4, 5, 0, 7, 8]],
+            GeoTransform::new(10.0, 1.0, 0.0, 2.0, 0.0, -1.0),
+            String::new(),
+            Some(0.0)).expect("new raster"));
+
+        // zones cover the left and right halves and the exterior
+        let zone = |id: &str, min_x: f64, max_x: f64| Zone {
+            geometry: Geometry::new(vec![vec![(min_x, 0.0), (max_x, 0.0),
+                (max_x, 2.0), (min_x, 2.0)]]),
+            id: id.to_string(),
+        };
+
+        let zones = vec![zone("left", 10.0, 12.0),
+            zone("right", 12.0, 14.0), zone("outside", 20.0, 22.0)];
+        let statistics = super::get_zonal_statistics(&raster,
+            &zones, &[0.0, 50.0, 100.0]).expect("zonal statistics");
+
+        assert_eq!(statistics.len(), 3);
+        assert_eq!(statistics["left"], vec![ZoneStatistics {
+            count: 3,
+            mean: Some(8.0 / 3.0),
+            percentiles: vec![1.0, 2.0, 5.0],
+            sum: 8.0,
+        }]);
+        assert_eq!(statistics["right"][0].count, 4);
+        assert_eq!(statistics["right"][0].mean, Some(5.5));
+
+        // zones without valid pixels report empty statistics
+        assert_eq!(statistics["outside"], vec![ZoneStatistics {
+            count: 0,
+            mean: None,
+            percentiles: Vec::new(),
+            sum: 0.0,
+        }]);
+
+        // duplicate zone ids are rejected
+        let zones = vec![zone("left", 10.0, 12.0), zone("left", 12.0, 14.0)];
+        assert!(super::get_zonal_statistics(&raster,
+            &zones, &[50.0]).is_err());
+
+        // rasters without a no_data value include every pixel
+        let raster = AnyRaster::UInt16(Raster::new(4, 2,
+            vec![vec![1, 2, 3, 4, 5, 0, 7, 8]],
+            GeoTransform::new(10.0, 1.0, 0.0, 2.0, 0.0, -1.0),
+            String::new(),
+            None).expect("new raster"));
+        let statistics = super::get_zonal_statistics(&raster,
+            &[zone("left", 10.0, 12.0)], &[0.0]).expect("zonal statistics");
+        assert_eq!(statistics["left"][0].count, 4);
+        assert_eq!(statistics["left"][0].percentiles, vec![0.0]);
+    }
+
+    #[cfg(feature = "gdal-backend")]
+    #[test]
+    fn zonal_statistics_band_no_data() {
+        let raster = AnyRaster::UInt16(Raster::new(2, 1,
+            vec![vec![0, 5], vec![0, 5]],
+            GeoTransform::new(10.0, 1.0, 0.0, 1.0, 0.0, -1.0),
+            String::new(),
+            Some(0.0)).expect("new raster"));
+
+        // each rasterband applies its own no_data value
+        let dataset = raster.to_dataset().expect("to dataset");
+        dataset.rasterband(2).expect("rasterband")
+            .set_no_data_value(5.0).expect("set no_data value");
+
+        let zone = Zone {
+            geometry: Geometry::new(vec![vec![(10.0, 0.0), (12.0, 0.0),
+                (12.0, 1.0), (10.0, 1.0)]]),
+            id: "zone".to_string(),
+        };
+
+        let statistics = super::get_zonal_statistics(&dataset,
+            &[zone], &[50.0]).expect("zonal statistics");
+        assert_eq!(statistics["zone"][0].sum, 5.0);
+        assert_eq!(statistics["zone"][1].sum, 0.0);
+        assert_eq!(statistics["zone"][1].count, 1);
+    }
+}